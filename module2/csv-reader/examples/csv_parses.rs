@@ -0,0 +1,59 @@
+// examples/csv_parses.rs
+// Simple CSV Parser – the step-by-step walkthrough from module2, now on top
+// of the crate's streaming reader
+//
+// Run with `cargo run --example csv_parses`.
+
+use csv_reader::Reader;
+use std::env;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("=== Simple CSV Parser ===\n");
+
+    // Example CSV content (we'll write it to a file first)
+    let csv_data = r#"
+name,age,city,"country, with comma"
+Alice,30,"New York, NY",USA
+Bob,25,London,"United Kingdom"
+"Charlie ""The Great""",42,Paris,France
+Diana,35,"São Paulo",Brazil
+"#;
+
+    // Write to a temporary file, leaving the working directory alone
+    let path = env::temp_dir().join(format!("csv_parses-{}.csv", std::process::id()));
+    std::fs::write(&path, csv_data.trim_start())?;
+    println!("Created {} with sample data\n", path.display());
+
+    // Parse the CSV
+    let records = parse_csv(&path);
+    std::fs::remove_file(&path)?;
+    let records = records?;
+
+    println!("Parsed {} records:\n", records.len());
+    for (i, record) in records.iter().enumerate() {
+        println!("Record {}: {:?}", i + 1, record);
+    }
+
+    Ok(())
+}
+
+// Main parser function
+// Streams the file through the reader, which ends a record only at a line
+// break outside quotes, so quoted fields may span several lines (RFC 4180).
+// A byte order mark, blank lines and a missing final line break are handled
+// there too.
+fn parse_csv(path: &Path) -> Result<Vec<Vec<String>>, Box<dyn std::error::Error>> {
+    let file = File::open(path)?;
+    let mut records = Vec::new();
+
+    for record in Reader::new(BufReader::new(file)) {
+        let record = record?;
+        println!("Line {} parsed into {} fields", record.line, record.fields.len());
+        records.push(record.fields);
+    }
+
+    Ok(records)
+}
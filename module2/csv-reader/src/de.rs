@@ -0,0 +1,255 @@
+// src/de.rs
+// Header-aware serde deserialization
+
+use crate::reader::{CsvError, Reader, Record};
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, Visitor};
+use serde::forward_to_deserialize_any;
+use std::fmt;
+use std::io::BufRead;
+use std::marker::PhantomData;
+
+// -----------------------------
+// Errors
+// -----------------------------
+// serde creates errors without knowing where they happened; the record and
+// field deserializers fill in the line and column on the way out.
+#[derive(Debug)]
+pub struct DeError {
+    pub line: usize,
+    pub column: Option<String>,
+    pub message: String,
+}
+
+impl DeError {
+    fn in_column(mut self, column: &str) -> Self {
+        self.column.get_or_insert_with(|| column.to_string());
+        self
+    }
+
+    fn at_line(mut self, line: usize) -> Self {
+        if self.line == 0 {
+            self.line = line;
+        }
+        self
+    }
+}
+
+impl fmt::Display for DeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.column {
+            Some(column) => write!(f, "line {}, column '{}': {}", self.line, column, self.message),
+            None => write!(f, "line {}: {}", self.line, self.message),
+        }
+    }
+}
+
+impl std::error::Error for DeError {}
+
+impl de::Error for DeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        DeError {
+            line: 0,
+            column: None,
+            message: msg.to_string(),
+        }
+    }
+}
+
+// -----------------------------
+// Typed Records
+// -----------------------------
+// The first record names the columns; every later record is mapped onto `T`
+// by those names, so column order does not matter and unknown columns are
+// ignored. Missing columns follow serde: `Option` fields become `None`,
+// `#[serde(default)]` fields get their default, anything else is an error.
+// Empty cells are `None` for `Option` fields.
+pub struct DeserializeRecords<R, T> {
+    reader: Reader<R>,
+    headers: Vec<String>,
+    _record: PhantomData<T>,
+}
+
+impl<R: BufRead> Reader<R> {
+    pub fn deserialize<T: DeserializeOwned>(mut self) -> Result<DeserializeRecords<R, T>, CsvError> {
+        let headers = match self.next() {
+            Some(header) => header?.fields,
+            None => Vec::new(),
+        };
+
+        Ok(DeserializeRecords {
+            reader: self,
+            headers,
+            _record: PhantomData,
+        })
+    }
+}
+
+impl<R: BufRead, T: DeserializeOwned> Iterator for DeserializeRecords<R, T> {
+    type Item = Result<T, CsvError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = match self.reader.next()? {
+            Ok(record) => record,
+            Err(err) => return Some(Err(err)),
+        };
+        Some(from_record(&self.headers, &record).map_err(CsvError::from))
+    }
+}
+
+pub fn from_record<T: DeserializeOwned>(headers: &[String], record: &Record) -> Result<T, DeError> {
+    T::deserialize(RecordDeserializer {
+        headers,
+        fields: &record.fields,
+    })
+    .map_err(|err| err.at_line(record.line))
+}
+
+// -----------------------------
+// Record Deserializer
+// -----------------------------
+// Presents a record as a map from column name to cell.
+struct RecordDeserializer<'a> {
+    headers: &'a [String],
+    fields: &'a [String],
+}
+
+impl<'de> de::Deserializer<'de> for RecordDeserializer<'de> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_map(RecordMap {
+            cells: self.headers.iter().zip(self.fields),
+            column: "",
+            value: "",
+        })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct RecordMap<'de, I> {
+    cells: I,
+    column: &'de str,
+    value: &'de str,
+}
+
+impl<'de, I> MapAccess<'de> for RecordMap<'de, I>
+where
+    I: Iterator<Item = (&'de String, &'de String)>,
+{
+    type Error = DeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, DeError> {
+        let Some((column, value)) = self.cells.next() else {
+            return Ok(None);
+        };
+        self.column = column;
+        self.value = value;
+        seed.deserialize(column.as_str().into_deserializer()).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, DeError> {
+        seed.deserialize(FieldDeserializer { value: self.value })
+            .map_err(|err| err.in_column(self.column))
+    }
+}
+
+// -----------------------------
+// Field Deserializer
+// -----------------------------
+// Converts one cell to whatever type the struct field asks for.
+struct FieldDeserializer<'de> {
+    value: &'de str,
+}
+
+impl FieldDeserializer<'_> {
+    fn invalid(&self, ty: &str, reason: impl fmt::Display) -> DeError {
+        de::Error::custom(format!("cannot parse {:?} as {}: {}", self.value, ty, reason))
+    }
+}
+
+// Numbers may be padded with spaces, e.g. in hand-aligned files.
+macro_rules! deserialize_number {
+    ($($method:ident => $visit:ident($ty:ty)),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+                match self.value.trim().parse::<$ty>() {
+                    Ok(n) => visitor.$visit(n),
+                    Err(err) => Err(self.invalid(stringify!($ty), err)),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for FieldDeserializer<'de> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_borrowed_str(self.value)
+    }
+
+    deserialize_number! {
+        deserialize_i8 => visit_i8(i8),
+        deserialize_i16 => visit_i16(i16),
+        deserialize_i32 => visit_i32(i32),
+        deserialize_i64 => visit_i64(i64),
+        deserialize_u8 => visit_u8(u8),
+        deserialize_u16 => visit_u16(u16),
+        deserialize_u32 => visit_u32(u32),
+        deserialize_u64 => visit_u64(u64),
+        deserialize_f32 => visit_f32(f32),
+        deserialize_f64 => visit_f64(f64),
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self.value.trim().to_ascii_lowercase().as_str() {
+            "true" | "yes" | "1" => visitor.visit_bool(true),
+            "false" | "no" | "0" => visitor.visit_bool(false),
+            _ => Err(self.invalid("bool", "expected true/false, yes/no or 1/0")),
+        }
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        let mut chars = self.value.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => visitor.visit_char(c),
+            _ => Err(self.invalid("char", "expected a single character")),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        if self.value.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    // Unit variants only, named by the cell text.
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        visitor.visit_enum(self.value.into_deserializer())
+    }
+
+    forward_to_deserialize_any! {
+        i128 u128 str string bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
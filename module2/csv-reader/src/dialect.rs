@@ -0,0 +1,186 @@
+// src/dialect.rs
+// Dialects and dialect sniffing
+
+use crate::reader::{CsvError, Reader};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// How much of the input `Dialect::sniff` looks at.
+pub const SNIFF_BYTES: usize = 4096;
+
+// Tried in this order; on a tie the earlier one wins.
+const DELIMITERS: [u8; 4] = [b',', b'\t', b';', b'|'];
+
+// -----------------------------
+// Dialect
+// -----------------------------
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Trim {
+    /// Keep every byte, as RFC 4180 says.
+    #[default]
+    None,
+    /// Drop spaces and tabs around unquoted fields and around quotes, but
+    /// keep them inside quoted fields.
+    Unquoted,
+    /// Also trim the inside of quoted fields.
+    All,
+}
+
+impl FromStr for Trim {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Ok(Trim::None),
+            "unquoted" => Ok(Trim::Unquoted),
+            "all" => Ok(Trim::All),
+            other => Err(format!("unknown trim policy '{}' (use none, unquoted or all)", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dialect {
+    pub delimiter: u8,
+    /// `None` turns quoting off, so quote characters are plain text.
+    pub quote: Option<u8>,
+    /// Escape character inside quoted fields, e.g. `\` for `\"`. Doubled
+    /// quotes are accepted either way.
+    pub escape: Option<u8>,
+    /// Lines starting with this are skipped (between records only).
+    pub comment: Option<String>,
+    pub trim: Trim,
+}
+
+// RFC 4180.
+impl Default for Dialect {
+    fn default() -> Self {
+        Dialect {
+            delimiter: b',',
+            quote: Some(b'"'),
+            escape: None,
+            comment: None,
+            trim: Trim::None,
+        }
+    }
+}
+
+impl Dialect {
+    pub fn validate(&self) -> Result<(), String> {
+        if matches!(self.delimiter, b'\r' | b'\n') {
+            return Err("the delimiter cannot be a line break".to_string());
+        }
+        if self.quote == Some(self.delimiter) {
+            return Err("the delimiter and the quote must differ".to_string());
+        }
+        if self.comment.as_deref() == Some("") {
+            return Err("the comment prefix cannot be empty".to_string());
+        }
+        Ok(())
+    }
+
+    /// Guesses the dialect from the start of a file: the delimiter that
+    /// splits the most lines into the same number (> 1) of fields, `#`
+    /// comments if the first line is one, backslash escapes if `\"` shows
+    /// up, and `Trim::Unquoted` if nearly every delimiter is followed by a
+    /// space. Falls back to RFC 4180 when nothing fits.
+    pub fn sniff(sample: &[u8]) -> Dialect {
+        let sample = sample.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(sample);
+        let sample = &sample[..sample.len().min(SNIFF_BYTES)];
+        // The last line is probably cut off.
+        let sample = match sample.iter().rposition(|&b| b == b'\n') {
+            Some(end) => &sample[..=end],
+            None => sample,
+        };
+
+        // Trimming while scoring lets `a, "b"` parse whatever the result.
+        let base = Dialect {
+            comment: sample.starts_with(b"#").then(|| "#".to_string()),
+            escape: sample.windows(2).any(|w| w == b"\\\"").then_some(b'\\'),
+            trim: Trim::Unquoted,
+            ..Dialect::default()
+        };
+
+        let mut best: Option<((bool, usize, usize), u8)> = None;
+        for delimiter in DELIMITERS {
+            let candidate = Dialect {
+                delimiter,
+                ..base.clone()
+            };
+            if let Some(score) = score(sample, candidate)
+                && best.is_none_or(|(best_score, _)| score > best_score)
+            {
+                best = Some((score, delimiter));
+            }
+        }
+
+        let delimiter = best.map_or(b',', |(_, delimiter)| delimiter);
+        Dialect {
+            delimiter,
+            trim: if padded(sample, delimiter) {
+                Trim::Unquoted
+            } else {
+                Trim::None
+            },
+            ..base
+        }
+    }
+}
+
+// (parsed without errors, records sharing the most common field count, that
+// count), compared in that order. `None` if no record has several fields.
+fn score(sample: &[u8], dialect: Dialect) -> Option<(bool, usize, usize)> {
+    let mut reader = Reader::new(sample).dialect(dialect).flexible(true);
+    let mut frequency: HashMap<usize, usize> = HashMap::new();
+    let mut clean = true;
+
+    while let Some(record) = reader.next_borrowed() {
+        match record {
+            Ok(record) => *frequency.entry(record.len()).or_insert(0) += 1,
+            // A quoted field cut off by the end of the sample.
+            Err(CsvError::UnterminatedQuote { .. }) => {}
+            Err(_) => clean = false,
+        }
+    }
+
+    let (fields, records) = frequency
+        .into_iter()
+        .max_by_key(|&(fields, records)| (records, fields))?;
+    (fields > 1).then_some((clean, records, fields))
+}
+
+// Whether at least four in five delimiters are followed by a space.
+fn padded(sample: &[u8], delimiter: u8) -> bool {
+    let mut total = 0;
+    let mut followed = 0;
+    for pair in sample.windows(2).filter(|pair| pair[0] == delimiter) {
+        total += 1;
+        if pair[1] == b' ' {
+            followed += 1;
+        }
+    }
+    total > 0 && followed * 5 >= total * 4
+}
+
+fn show(byte: Option<u8>) -> String {
+    match byte {
+        Some(b'\t') => "tab".to_string(),
+        Some(byte) => format!("'{}'", byte as char),
+        None => "none".to_string(),
+    }
+}
+
+impl fmt::Display for Dialect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "delimiter {}, quote {}, escape {}, comment {}, trim {}",
+            show(Some(self.delimiter)),
+            show(self.quote),
+            show(self.escape),
+            self.comment.as_deref().map_or("none".to_string(), |c| format!("'{}'", c)),
+            format!("{:?}", self.trim).to_lowercase()
+        )
+    }
+}
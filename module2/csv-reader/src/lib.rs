@@ -0,0 +1,10 @@
+// src/lib.rs
+// Streaming RFC 4180 reader, shared by the CSVparser binary and the
+// benchmarks in module3
+
+pub mod de;
+pub mod dialect;
+pub mod reader;
+
+pub use dialect::Dialect;
+pub use reader::{BorrowedRecord, CsvError, Reader, Record};
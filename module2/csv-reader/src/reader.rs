@@ -0,0 +1,465 @@
+// src/reader.rs
+// Streaming RFC 4180 reader
+
+use crate::de::DeError;
+use crate::dialect::{Dialect, Trim};
+use std::borrow::Cow;
+use std::io::{self, BufRead};
+use thiserror::Error;
+
+// -----------------------------
+// Errors
+// -----------------------------
+// Lines and columns are 1-based; columns count characters, not bytes.
+#[derive(Error, Debug)]
+pub enum CsvError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("line {line}, column {column}: quoted field is never closed")]
+    UnterminatedQuote { line: usize, column: usize },
+
+    #[error("line {line}, column {column}: unexpected {found:?} after closing quote")]
+    TextAfterQuote { line: usize, column: usize, found: char },
+
+    #[error("line {line}, column {column}: quote inside an unquoted field")]
+    StrayQuote { line: usize, column: usize },
+
+    #[error("line {line}, column {column}: invalid UTF-8")]
+    InvalidUtf8 { line: usize, column: usize },
+
+    #[error("line {line}: expected {expected} fields, found {found}")]
+    FieldCount {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+
+    #[error(transparent)]
+    Deserialize(#[from] DeError),
+}
+
+impl CsvError {
+    /// Where the error happened, if it is about a particular line.
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            CsvError::Io(_) => None,
+            CsvError::UnterminatedQuote { line, .. }
+            | CsvError::TextAfterQuote { line, .. }
+            | CsvError::StrayQuote { line, .. }
+            | CsvError::InvalidUtf8 { line, .. }
+            | CsvError::FieldCount { line, .. } => Some(*line),
+            CsvError::Deserialize(err) => Some(err.line),
+        }
+    }
+}
+
+// -----------------------------
+// Records
+// -----------------------------
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    /// Line the record starts on; a quoted field may carry it over several.
+    pub line: usize,
+    pub fields: Vec<String>,
+}
+
+// A field's bytes within the record buffer, quotes excluded.
+#[derive(Debug, Clone, Copy)]
+struct Span {
+    start: usize,
+    end: usize,
+    // Contains doubled quotes that still need collapsing.
+    unescape: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    FieldStart,
+    Unquoted,
+    Quoted,
+    // Just saw a quote inside a quoted field: either the closing quote or
+    // the first half of an escaped `""`.
+    AfterQuote,
+    // Just saw the escape character; the next byte is taken as it is.
+    Escaped,
+    // Trimming the blanks after a closing quote.
+    Closed,
+}
+
+// -----------------------------
+// Reader
+// -----------------------------
+// Reads one physical line at a time and keeps appending lines to the same
+// record while a quoted field is open, so embedded line breaks (LF or CRLF)
+// are kept as they are. Delimiter, quoting, escapes, comments and trimming
+// follow the dialect (RFC 4180 unless set). Blank lines between records are
+// skipped, a UTF-8 byte order mark at the start is dropped and the last
+// record does not need a line break.
+//
+// After an error the rest of the offending line is skipped and reading
+// continues with the next record.
+pub struct Reader<R> {
+    input: R,
+    raw: Vec<u8>,
+    spans: Vec<Span>,
+    line: usize,
+    record_line: usize,
+    expected_fields: Option<usize>,
+    dialect: Dialect,
+    flexible: bool,
+    failed: bool,
+}
+
+impl<R: BufRead> Reader<R> {
+    pub fn new(input: R) -> Self {
+        Reader {
+            input,
+            raw: Vec::new(),
+            spans: Vec::new(),
+            line: 0,
+            record_line: 0,
+            expected_fields: None,
+            dialect: Dialect::default(),
+            flexible: false,
+            failed: false,
+        }
+    }
+
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Continue in the middle of a file: `lines` lines come before the input
+    /// and, unless flexible, records must have `expected_fields` fields.
+    pub fn resume_at(mut self, lines: usize, expected_fields: Option<usize>) -> Self {
+        self.line = lines;
+        self.expected_fields = expected_fields;
+        self
+    }
+
+    /// Allow records with a different number of fields than the first one.
+    pub fn flexible(mut self, flexible: bool) -> Self {
+        self.flexible = flexible;
+        self
+    }
+
+    // Fills `raw` and `spans` with the next record; `false` at end of input.
+    fn read_raw(&mut self) -> Result<bool, CsvError> {
+        self.raw.clear();
+        self.spans.clear();
+
+        let delimiter = self.dialect.delimiter;
+        let quote = self.dialect.quote;
+        let escape = self.dialect.escape.filter(|&escape| Some(escape) != quote);
+        let trim = self.dialect.trim != Trim::None;
+
+        let mut state = State::FieldStart;
+        let mut field_start = 0;
+        let mut unescape = false;
+        let mut opening_quote = 0;
+        let mut closing_quote = 0;
+
+        loop {
+            let line_start = self.raw.len();
+            if self.input.read_until(b'\n', &mut self.raw)? == 0 {
+                // End of input: a final line without a line break still counts.
+                return match state {
+                    _ if self.raw.is_empty() => Ok(false),
+                    // Only blanks (trimmed) or a comment on the last line.
+                    State::FieldStart if self.spans.is_empty() => Ok(false),
+                    State::Quoted | State::Escaped => {
+                        let (line, column) = self.locate(opening_quote);
+                        Err(CsvError::UnterminatedQuote { line, column })
+                    }
+                    _ => {
+                        let end = self.raw.len();
+                        self.push_field(state, field_start, end, closing_quote, unescape);
+                        self.finish_record()
+                    }
+                };
+            }
+
+            self.line += 1;
+            if line_start == 0 {
+                self.record_line = self.line;
+                if self.line == 1 && self.raw.starts_with(b"\xEF\xBB\xBF") {
+                    self.raw.drain(..3);
+                }
+                let comment = self.dialect.comment.as_ref();
+                if comment.is_some_and(|prefix| self.raw.starts_with(prefix.as_bytes())) {
+                    self.raw.clear();
+                    continue;
+                }
+            }
+
+            let mut i = line_start;
+            while i < self.raw.len() {
+                let byte = self.raw[i];
+                let line_break = byte == b'\n'
+                    || (byte == b'\r' && matches!(self.raw.get(i + 1), Some(b'\n') | None));
+
+                match state {
+                    State::Quoted if Some(byte) == quote => {
+                        state = State::AfterQuote;
+                        closing_quote = i;
+                    }
+                    State::Quoted if Some(byte) == escape => {
+                        unescape = true;
+                        state = State::Escaped;
+                    }
+                    State::Quoted => {}
+                    State::Escaped => state = State::Quoted,
+                    State::AfterQuote if Some(byte) == quote => {
+                        unescape = true;
+                        state = State::Quoted;
+                    }
+                    _ if byte == delimiter => {
+                        self.push_field(state, field_start, i, closing_quote, unescape);
+                        state = State::FieldStart;
+                        field_start = i + 1;
+                        unescape = false;
+                    }
+                    State::FieldStart if line_break && self.spans.is_empty() => {
+                        // Blank line between records.
+                        self.raw.clear();
+                        break;
+                    }
+                    _ if line_break => {
+                        self.push_field(state, field_start, i, closing_quote, unescape);
+                        return self.finish_record();
+                    }
+                    State::FieldStart | State::Closed if trim && is_blank(byte) => {}
+                    State::AfterQuote if trim && is_blank(byte) => state = State::Closed,
+                    State::FieldStart if Some(byte) == quote => {
+                        state = State::Quoted;
+                        field_start = i + 1;
+                        opening_quote = i;
+                    }
+                    State::FieldStart => {
+                        state = State::Unquoted;
+                        field_start = i;
+                    }
+                    State::Unquoted if Some(byte) == quote => {
+                        let (line, column) = self.locate(i);
+                        return Err(CsvError::StrayQuote { line, column });
+                    }
+                    State::Unquoted => {}
+                    State::AfterQuote | State::Closed => {
+                        let (line, column) = self.locate(i);
+                        let found = String::from_utf8_lossy(&self.raw[i..])
+                            .chars()
+                            .next()
+                            .unwrap_or_default();
+                        return Err(CsvError::TextAfterQuote {
+                            line,
+                            column,
+                            found,
+                        });
+                    }
+                }
+                i += 1;
+            }
+        }
+    }
+
+    // `end` is where the field stopped: the delimiter, line break or end of
+    // input. Quoted fields end at their closing quote instead.
+    fn push_field(&mut self, state: State, start: usize, end: usize, closing_quote: usize, unescape: bool) {
+        let mut span = match state {
+            State::FieldStart => Span {
+                start: end,
+                end,
+                unescape: false,
+            },
+            State::AfterQuote | State::Closed => Span {
+                start,
+                end: closing_quote,
+                unescape,
+            },
+            State::Unquoted | State::Quoted | State::Escaped => Span {
+                start,
+                end,
+                unescape: false,
+            },
+        };
+
+        let trim = match state {
+            State::Unquoted => self.dialect.trim != Trim::None,
+            State::AfterQuote | State::Closed => self.dialect.trim == Trim::All,
+            _ => false,
+        };
+        if trim {
+            while span.start < span.end && is_blank(self.raw[span.start]) {
+                span.start += 1;
+            }
+            while span.end > span.start && is_blank(self.raw[span.end - 1]) {
+                span.end -= 1;
+            }
+        }
+
+        self.spans.push(span);
+    }
+
+    fn finish_record(&mut self) -> Result<bool, CsvError> {
+        if let Err(err) = std::str::from_utf8(&self.raw) {
+            let (line, column) = self.locate(err.valid_up_to());
+            return Err(CsvError::InvalidUtf8 { line, column });
+        }
+
+        let found = self.spans.len();
+        match self.expected_fields {
+            None => self.expected_fields = Some(found),
+            Some(expected) if expected != found && !self.flexible => {
+                return Err(CsvError::FieldCount {
+                    line: self.record_line,
+                    expected,
+                    found,
+                });
+            }
+            Some(_) => {}
+        }
+
+        Ok(true)
+    }
+
+    // Line and column of a byte offset into the current record.
+    fn locate(&self, offset: usize) -> (usize, usize) {
+        let before = &self.raw[..offset];
+        let line_start = before
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |pos| pos + 1);
+        let newlines = before.iter().filter(|&&b| b == b'\n').count();
+        let column = String::from_utf8_lossy(&before[line_start..]).chars().count() + 1;
+
+        (self.record_line + newlines, column)
+    }
+
+    fn borrowed(&self) -> BorrowedRecord<'_> {
+        BorrowedRecord {
+            line: self.record_line,
+            text: std::str::from_utf8(&self.raw).expect("checked in finish_record"),
+            spans: &self.spans,
+            dialect: &self.dialect,
+        }
+    }
+
+    // Shared by both ways of reading: `None` at the end, or once the input
+    // itself has failed (a broken stream will not recover; syntax errors do).
+    fn advance(&mut self) -> Option<Result<(), CsvError>> {
+        if self.failed {
+            return None;
+        }
+
+        match self.read_raw() {
+            Ok(true) => Some(Ok(())),
+            Ok(false) => None,
+            Err(err) => {
+                self.failed = matches!(err, CsvError::Io(_));
+                Some(Err(err))
+            }
+        }
+    }
+
+    /// Like `next`, but the record borrows the reader's buffer instead of
+    /// allocating a `String` per field. The buffer is reused, so the record
+    /// has to be dropped before reading the next one:
+    ///
+    /// ```text
+    /// while let Some(record) = reader.next_borrowed() { ... }
+    /// ```
+    pub fn next_borrowed(&mut self) -> Option<Result<BorrowedRecord<'_>, CsvError>> {
+        match self.advance()? {
+            Ok(()) => Some(Ok(self.borrowed())),
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Reader<R> {
+    type Item = Result<Record, CsvError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.advance()? {
+            Ok(()) => Some(Ok(self.borrowed().into_record())),
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+// -----------------------------
+// Borrowed Records
+// -----------------------------
+// Fields are slices of the reader's buffer. Only a quoted field with
+// doubled quotes or escapes has to be copied, to collapse `""` into `"`.
+#[derive(Debug, Clone, Copy)]
+pub struct BorrowedRecord<'r> {
+    line: usize,
+    text: &'r str,
+    spans: &'r [Span],
+    dialect: &'r Dialect,
+}
+
+impl<'r> BorrowedRecord<'r> {
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<Cow<'r, str>> {
+        self.spans.get(index).map(|span| self.field(span))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Cow<'r, str>> + '_ {
+        self.spans.iter().map(|span| self.field(span))
+    }
+
+    pub fn into_record(self) -> Record {
+        Record {
+            line: self.line,
+            fields: self.iter().map(Cow::into_owned).collect(),
+        }
+    }
+
+    fn field(&self, span: &Span) -> Cow<'r, str> {
+        let field = &self.text[span.start..span.end];
+        if span.unescape {
+            Cow::Owned(unescape(field, self.dialect))
+        } else {
+            Cow::Borrowed(field)
+        }
+    }
+}
+
+// Inside a closed quoted field a quote only appears doubled and an escape
+// always has a character after it, so both just keep the next character.
+fn unescape(field: &str, dialect: &Dialect) -> String {
+    let special = |c: char| {
+        dialect.quote.is_some_and(|quote| c == quote as char)
+            || dialect.escape.is_some_and(|escape| c == escape as char)
+    };
+
+    let mut unescaped = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        if special(c) {
+            unescaped.extend(chars.next());
+        } else {
+            unescaped.push(c);
+        }
+    }
+    unescaped
+}
+
+pub fn is_blank(byte: u8) -> bool {
+    byte == b' ' || byte == b'\t'
+}
@@ -0,0 +1,324 @@
+// src/bin/CSVparser/main.rs
+// Simple CSV Parser – RFC 4180 streaming reader
+//
+// Usage: CSVparser [path] [--flexible] [--sniff] [--delimiter c]
+//                  [--quote c|none] [--escape c|none] [--comment prefix]
+//                  [--trim none|unquoted|all]
+//                  [--parallel] [--threads n] [--chunk-size bytes]
+//                  [--schema schema.json] [--report text|json]
+// Without a path a sample data.csv is written and parsed, then read again
+// as typed `Person` records. `--sniff` guesses the dialect from the start of
+// the file; the other dialect flags override the guess. `--parallel` (implied
+// by `--threads` and `--chunk-size`) times chunked parsing on a rayon pool
+// against the single reader and checks both agree. `--schema` checks every
+// record against a schema (see schema.rs) and prints all violations; the
+// exit code is 1 if there are any.
+
+mod parallel;
+mod schema;
+
+// The reader lives in its own crate so the benchmarks can use it too.
+use csv_reader::{dialect, reader};
+
+use dialect::{Dialect, SNIFF_BYTES};
+use parallel::parse_parallel;
+use reader::{CsvError, Reader, Record};
+use schema::Schema;
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::env;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::time::Instant;
+
+// Columns are matched by header name, so their order in the file is free.
+#[derive(Debug, Deserialize)]
+struct Person {
+    name: String,
+    age: u32,
+    city: String,
+    #[serde(rename = "country, with comma")]
+    country: String,
+    // Empty cells and a missing column both become `None`.
+    note: Option<String>,
+    // Not in the sample file, so the default is used.
+    #[serde(default)]
+    active: bool,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let options = parse_args(env::args().skip(1))?;
+    let flexible = options.flexible;
+
+    // A JSON report is for other programs, so it is all that goes to stdout.
+    let chatty = !options.json_report;
+    if chatty {
+        println!("=== Simple CSV Parser ===\n");
+    }
+
+    let sample = options.path.is_none();
+    let path = match options.path.clone() {
+        Some(path) => path,
+        None => {
+            let csv_data = r#"
+name,age,city,"country, with comma",note
+Alice,30,"New York, NY",USA,
+Bob,25,London,"United Kingdom","Prefers ""Bobby""
+on weekends"
+"Charlie ""The Great""",42,Paris,France,  keeps its spaces
+Diana,35,"São Paulo",Brazil,no line break at the end
+"#;
+
+            // Byte order mark, CRLF line breaks and a final line without one,
+            // as spreadsheet exports tend to produce.
+            let exported = format!(
+                "\u{FEFF}{}",
+                csv_data.trim_start().trim_end().replace('\n', "\r\n")
+            );
+            std::fs::write("data.csv", exported)?;
+            if chatty {
+                println!("Created data.csv with sample data\n");
+            }
+            "data.csv".to_string()
+        }
+    };
+
+    let mut dialect = if options.sniff {
+        let mut head = Vec::new();
+        File::open(&path)?
+            .take(SNIFF_BYTES as u64)
+            .read_to_end(&mut head)?;
+        Dialect::sniff(&head)
+    } else {
+        Dialect::default()
+    };
+    for (flag, value) in &options.dialect_flags {
+        apply_flag(&mut dialect, flag, value)?;
+    }
+    dialect.validate()?;
+    if chatty {
+        println!("Dialect: {}\n", dialect);
+    }
+
+    if let Some(schema_path) = &options.schema {
+        let schema = match Schema::load(schema_path) {
+            Ok(schema) => schema,
+            Err(err) => {
+                eprintln!("{}: {}", schema_path, err);
+                std::process::exit(1);
+            }
+        };
+        let reader = Reader::new(BufReader::new(File::open(&path)?))
+            .dialect(dialect)
+            .flexible(flexible);
+        let report = schema::validate(&schema, reader);
+        if options.json_report {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            print!("{}", report);
+        }
+        std::process::exit(if report.is_valid() { 0 } else { 1 });
+    }
+
+    if options.parallel {
+        return compare_parallel(&path, &dialect, &options);
+    }
+
+    let records = match parse_csv(&path, &dialect, flexible) {
+        Ok(records) => records,
+        Err(err) => {
+            eprintln!("{}: {}", path, err);
+            std::process::exit(1);
+        }
+    };
+
+    println!("Parsed {} records:\n", records.len());
+    for (i, record) in records.iter().enumerate() {
+        println!("Record {} (line {}): {:?}", i + 1, record.line, record.fields);
+    }
+
+    // Same file again without a String per field: fields point into the
+    // reader's buffer and only those with doubled quotes are copied.
+    let file = File::open(&path)?;
+    let mut reader = Reader::new(BufReader::new(file))
+        .dialect(dialect.clone())
+        .flexible(flexible);
+    let (mut borrowed, mut copied) = (0, 0);
+    while let Some(record) = reader.next_borrowed() {
+        for field in record?.iter() {
+            match field {
+                Cow::Borrowed(_) => borrowed += 1,
+                Cow::Owned(_) => copied += 1,
+            }
+        }
+    }
+    println!(
+        "\nZero-copy pass: {} fields borrowed, {} copied to unescape quotes",
+        borrowed, copied
+    );
+
+    if sample {
+        println!("\nTyped records:\n");
+        let file = File::open(&path)?;
+        let reader = Reader::new(BufReader::new(file)).dialect(dialect);
+        for person in reader.deserialize::<Person>()? {
+            match person {
+                Ok(person) => println!(
+                    "{} ({}) from {}, {}{}{}",
+                    person.name,
+                    person.age,
+                    person.city,
+                    person.country,
+                    person.note.map(|n| format!(" – {:?}", n)).unwrap_or_default(),
+                    if person.active { " [active]" } else { "" }
+                ),
+                Err(err) => println!("Skipped: {}", err),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Stops at the first malformed record.
+fn parse_csv(path: &str, dialect: &Dialect, flexible: bool) -> Result<Vec<Record>, CsvError> {
+    let file = File::open(path)?;
+    Reader::new(BufReader::new(file))
+        .dialect(dialect.clone())
+        .flexible(flexible)
+        .collect()
+}
+
+// Parses the file both ways and checks they agree.
+fn compare_parallel(
+    path: &str,
+    dialect: &Dialect,
+    options: &Options,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Each run is timed without the other's records alive; otherwise the
+    // second one also pays for growing the heap. The sequential parse is
+    // repeated afterwards for the comparison.
+    let start = Instant::now();
+    drop(parse_csv(path, dialect, options.flexible));
+    let seq_time = start.elapsed();
+
+    // 0 threads lets rayon pick one per core.
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(options.threads.unwrap_or(0))
+        .build()?;
+    let start = Instant::now();
+    let data = std::fs::read(path)?;
+    let parallel = pool.install(|| parse_parallel(&data, dialect, options.flexible, options.chunk_size));
+    let par_time = start.elapsed();
+
+    let sequential = parse_csv(path, dialect, options.flexible);
+
+    let outcome = |result: &Result<Vec<Record>, CsvError>| match result {
+        Ok(records) => format!("{} records", records.len()),
+        Err(err) => format!("error: {}", err),
+    };
+    let same = match (&sequential, &parallel) {
+        (Ok(a), Ok(b)) => a == b,
+        (Err(a), Err(b)) => a.to_string() == b.to_string(),
+        _ => false,
+    };
+    let speedup = seq_time.as_secs_f64() / par_time.as_secs_f64().max(0.001);
+
+    println!("Results:");
+    println!("   Sequential     : {}", outcome(&sequential));
+    println!("   Parallel       : {}", outcome(&parallel));
+    println!("   Match          : {}", if same { "YES" } else { "NO" });
+    println!("   Threads        : {}", pool.current_num_threads());
+    println!("   Sequential time: {:.3} s", seq_time.as_secs_f64());
+    println!("   Parallel time  : {:.3} s", par_time.as_secs_f64());
+    println!("   Speedup        : {:.1}x", speedup);
+
+    Ok(())
+}
+
+// -----------------------------
+// Command Line
+// -----------------------------
+#[derive(Debug, Default)]
+struct Options {
+    path: Option<String>,
+    flexible: bool,
+    sniff: bool,
+    parallel: bool,
+    threads: Option<usize>,
+    chunk_size: Option<usize>,
+    schema: Option<String>,
+    json_report: bool,
+    // Applied in order on top of the default or sniffed dialect.
+    dialect_flags: Vec<(String, String)>,
+}
+
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.peekable();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--flexible" => options.flexible = true,
+            "--sniff" => options.sniff = true,
+            "--parallel" => options.parallel = true,
+            "--schema" => {
+                options.schema = Some(args.next().ok_or("--schema needs a path")?);
+            }
+            "--report" => match args.next().as_deref() {
+                Some("text") => options.json_report = false,
+                Some("json") => options.json_report = true,
+                _ => return Err("--report expects text or json".to_string()),
+            },
+            "--threads" | "--chunk-size" => {
+                let value = args.next().ok_or_else(|| format!("{} needs a value", arg))?;
+                let n = value
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| format!("{} expects a positive number, got '{}'", arg, value))?;
+                if arg == "--threads" {
+                    options.threads = Some(n);
+                } else {
+                    options.chunk_size = Some(n);
+                }
+                options.parallel = true;
+            }
+            "--delimiter" | "--quote" | "--escape" | "--comment" | "--trim" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("{} needs a value", arg))?;
+                options.dialect_flags.push((arg, value));
+            }
+            flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+            _ if options.path.is_none() => options.path = Some(arg),
+            _ => return Err(format!("unexpected argument '{}'", arg)),
+        }
+    }
+
+    Ok(options)
+}
+
+fn apply_flag(dialect: &mut Dialect, flag: &str, value: &str) -> Result<(), String> {
+    match flag {
+        "--delimiter" => dialect.delimiter = parse_byte(flag, value)?,
+        "--quote" if value == "none" => dialect.quote = None,
+        "--quote" => dialect.quote = Some(parse_byte(flag, value)?),
+        "--escape" if value == "none" => dialect.escape = None,
+        "--escape" => dialect.escape = Some(parse_byte(flag, value)?),
+        "--comment" if value == "none" => dialect.comment = None,
+        "--comment" => dialect.comment = Some(value.to_string()),
+        "--trim" => dialect.trim = value.parse()?,
+        _ => unreachable!("checked in parse_args"),
+    }
+    Ok(())
+}
+
+// A single ASCII character; `tab` and `\t` stand for a tab.
+fn parse_byte(flag: &str, value: &str) -> Result<u8, String> {
+    match value {
+        "tab" | "\\t" => Ok(b'\t'),
+        _ if value.len() == 1 && value.is_ascii() => Ok(value.as_bytes()[0]),
+        _ => Err(format!("{} expects a single ASCII character, got '{}'", flag, value)),
+    }
+}
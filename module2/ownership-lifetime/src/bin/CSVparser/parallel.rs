@@ -0,0 +1,229 @@
+// src/bin/CSVparser/parallel.rs
+// Parallel chunked parsing
+
+use crate::dialect::{Dialect, Trim};
+use crate::reader::{is_blank, CsvError, Reader, Record};
+use rayon::prelude::*;
+
+/// Chunks smaller than this are not worth a task of their own.
+pub const MIN_CHUNK_BYTES: usize = 1 << 20;
+
+// -----------------------------
+// Chunk Boundaries
+// -----------------------------
+// A chunk may only end right after the line break that ends a record, never
+// inside a quoted field. Whether a line break is quoted depends on
+// everything before it, so this is one sequential pass that follows the
+// reader's quoting rules (quotes open only at a field start, doubled quotes,
+// escapes, comment lines) without building any fields.
+#[derive(Debug)]
+struct Chunk {
+    start: usize,
+    end: usize,
+    // Lines before `start`, so record and error lines stay file-relative.
+    lines_before: usize,
+}
+
+fn split(data: &[u8], dialect: &Dialect, chunk_size: usize) -> Vec<Chunk> {
+    let delimiter = dialect.delimiter;
+    let quote = dialect.quote;
+    let escape = dialect.escape.filter(|&escape| Some(escape) != quote);
+    let comment = dialect.comment.as_deref().map(str::as_bytes);
+    let trim = dialect.trim != Trim::None;
+
+    let mut chunks = Vec::new();
+    let mut chunk_start = 0;
+    let mut chunk_lines = 0;
+    let mut lines = 0;
+
+    let mut in_quotes = false;
+    let mut line_start = true;
+    let mut field_start = true;
+    let mut i = if data.starts_with(b"\xEF\xBB\xBF") { 3 } else { 0 };
+
+    while i < data.len() {
+        if line_start && comment.is_some_and(|prefix| data[i..].starts_with(prefix)) {
+            // Go straight to the comment's line break.
+            match data[i..].iter().position(|&b| b == b'\n') {
+                Some(offset) => i += offset,
+                None => break,
+            }
+        }
+        line_start = false;
+
+        let byte = data[i];
+        if in_quotes {
+            if Some(byte) == quote {
+                if data.get(i + 1) == Some(&byte) {
+                    i += 1;
+                } else {
+                    in_quotes = false;
+                }
+            } else if Some(byte) == escape {
+                i += 1;
+                if data.get(i) == Some(&b'\n') {
+                    lines += 1;
+                }
+            } else if byte == b'\n' {
+                lines += 1;
+            }
+        } else if byte == b'\n' {
+            lines += 1;
+            line_start = true;
+            field_start = true;
+            if i + 1 - chunk_start >= chunk_size {
+                chunks.push(Chunk {
+                    start: chunk_start,
+                    end: i + 1,
+                    lines_before: chunk_lines,
+                });
+                chunk_start = i + 1;
+                chunk_lines = lines;
+            }
+        } else if byte == delimiter {
+            field_start = true;
+        } else if field_start && Some(byte) == quote {
+            in_quotes = true;
+            field_start = false;
+        } else if !(field_start && trim && is_blank(byte)) {
+            field_start = false;
+        }
+        i += 1;
+    }
+
+    if chunk_start < data.len() {
+        chunks.push(Chunk {
+            start: chunk_start,
+            end: data.len(),
+            lines_before: chunk_lines,
+        });
+    }
+    chunks
+}
+
+// -----------------------------
+// Parallel Parsing
+// -----------------------------
+/// Same result as reading `data` with one `Reader` and collecting, including
+/// which error comes out first, but the chunks are parsed on the current
+/// rayon pool. Without a `chunk_size` each thread gets about four chunks of
+/// at least `MIN_CHUNK_BYTES`.
+pub fn parse_parallel(
+    data: &[u8],
+    dialect: &Dialect,
+    flexible: bool,
+    chunk_size: Option<usize>,
+) -> Result<Vec<Record>, CsvError> {
+    let chunk_size = chunk_size
+        .unwrap_or_else(|| (data.len() / (rayon::current_num_threads() * 4)).max(MIN_CHUNK_BYTES))
+        .max(1);
+
+    // Later chunks check their field counts against the file's first record.
+    let expected_fields = match Reader::new(data).dialect(dialect.clone()).next_borrowed() {
+        Some(Ok(first)) if !flexible => Some(first.len()),
+        _ => None,
+    };
+
+    let chunks = split(data, dialect, chunk_size);
+    let parsed: Vec<Result<Vec<Record>, CsvError>> = chunks
+        .par_iter()
+        .map(|chunk| {
+            Reader::new(&data[chunk.start..chunk.end])
+                .dialect(dialect.clone())
+                .flexible(flexible)
+                .resume_at(chunk.lines_before, expected_fields)
+                .collect()
+        })
+        .collect();
+
+    // In file order, so the first error is the one a single reader would hit.
+    let mut records = Vec::new();
+    for chunk in parsed {
+        records.extend(chunk?);
+    }
+    Ok(records)
+}
+
+// -----------------------------
+// Tests
+// -----------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Errors compared by message, which carries the line and column.
+    fn sequential(data: &[u8], dialect: &Dialect, flexible: bool) -> Result<Vec<Record>, String> {
+        Reader::new(data)
+            .dialect(dialect.clone())
+            .flexible(flexible)
+            .collect::<Result<_, _>>()
+            .map_err(|err: CsvError| err.to_string())
+    }
+
+    // Every chunk size from one byte up, so each boundary lands everywhere.
+    fn assert_same_as_sequential(data: &str, dialect: &Dialect) {
+        let data = data.as_bytes();
+        for flexible in [false, true] {
+            let expected = sequential(data, dialect, flexible);
+            for chunk_size in 1..=data.len() + 1 {
+                let chunks = split(data, dialect, chunk_size);
+                assert_eq!(chunks.first().map_or(0, |chunk| chunk.start), 0);
+                assert_eq!(chunks.last().map_or(0, |chunk| chunk.end), data.len());
+                assert!(chunks.windows(2).all(|pair| pair[0].end == pair[1].start));
+
+                let parsed = parse_parallel(data, dialect, flexible, Some(chunk_size))
+                    .map_err(|err| err.to_string());
+                assert_eq!(parsed, expected, "chunk size {} of {:?}", chunk_size, data);
+            }
+        }
+    }
+
+    #[test]
+    fn quoted_line_breaks_never_split_a_record() {
+        let dialect = Dialect::default();
+        assert_same_as_sequential(
+            "name,note\n\"a\nb\",1\n\"say \"\"hi\"\"\n\",2\n\"\n\n\",3\nlast,4",
+            &dialect,
+        );
+        assert_same_as_sequential("\u{feff}a,b\r\n\"x\r\ny\",1\r\n\r\nz,2\r\n", &dialect);
+        // A quote inside an unquoted field does not open a quoted one.
+        assert_same_as_sequential("a,b\nx\"y,\"1\n2\"\n", &dialect);
+    }
+
+    #[test]
+    fn follows_the_dialect_when_splitting() {
+        let dialect = Dialect {
+            delimiter: b';',
+            escape: Some(b'\\'),
+            comment: Some("#".to_string()),
+            trim: Trim::Unquoted,
+            ..Dialect::default()
+        };
+        assert_same_as_sequential(
+            "a;b\n# a \"comment\n  \"x\\\"\ny\"  ;1\n\"\\\n\";2\n#\nc;3\n",
+            &dialect,
+        );
+
+        let unquoted = Dialect {
+            quote: None,
+            ..Dialect::default()
+        };
+        assert_same_as_sequential("a,b\n\"x,1\ny\",2\n", &unquoted);
+    }
+
+    #[test]
+    fn reports_the_first_error_in_file_order() {
+        let dialect = Dialect::default();
+        let cases = [
+            // Field counts checked against the first record across chunks.
+            ("a,b\n1,2\n3\n4,5,6\n\"7\n\",8\n", "line 3: expected 2 fields, found 1"),
+            // An error early, another later.
+            ("a,b\n\"1\"x,2\n3\n", "line 2, column 4: unexpected 'x' after closing quote"),
+            ("a,b\n1,2\n\"3,4\n5,6\n", "line 3, column 1: quoted field is never closed"),
+        ];
+        for (data, error) in cases {
+            assert_eq!(sequential(data.as_bytes(), &dialect, false), Err(error.to_string()));
+            assert_same_as_sequential(data, &dialect);
+        }
+    }
+}
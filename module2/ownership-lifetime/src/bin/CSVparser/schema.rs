@@ -0,0 +1,373 @@
+// src/bin/CSVparser/schema.rs
+// Schema validation and violation reports
+//
+// A schema is a JSON file:
+//
+//     {
+//       "columns": [
+//         { "name": "name", "required": true, "pattern": "^[A-Z]" },
+//         { "name": "age", "type": "integer", "min": 0, "max": 150 },
+//         { "name": "joined", "type": "date" }
+//       ],
+//       "allow_extra_columns": false
+//     }
+//
+// Types are string (default), integer, float, bool (true/yes/1, false/no/0)
+// and date (YYYY-MM-DD). `min`/`max` only apply to numbers, `pattern` is
+// matched against the raw cell. Empty cells only fail `required`.
+
+use crate::reader::{CsvError, Reader};
+use chrono::NaiveDate;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt;
+use std::io::{self, BufRead};
+use std::path::Path;
+use thiserror::Error;
+
+// -----------------------------
+// Errors
+// -----------------------------
+#[derive(Error, Debug)]
+pub enum SchemaError {
+    #[error("cannot read schema: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("invalid schema: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("column '{column}': invalid pattern: {source}")]
+    Pattern { column: String, source: regex::Error },
+
+    #[error("column '{column}': {message}")]
+    Column { column: String, message: String },
+}
+
+// -----------------------------
+// Schema
+// -----------------------------
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnType {
+    #[default]
+    String,
+    Integer,
+    Float,
+    Bool,
+    Date,
+}
+
+impl fmt::Display for ColumnType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ColumnType::String => "string",
+            ColumnType::Integer => "integer",
+            ColumnType::Float => "float",
+            ColumnType::Bool => "bool",
+            ColumnType::Date => "date",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ColumnSpec {
+    name: String,
+    #[serde(rename = "type", default)]
+    ty: ColumnType,
+    #[serde(default)]
+    required: bool,
+    pattern: Option<String>,
+    min: Option<f64>,
+    max: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SchemaSpec {
+    columns: Vec<ColumnSpec>,
+    #[serde(default)]
+    allow_extra_columns: bool,
+}
+
+#[derive(Debug)]
+struct Column {
+    spec: ColumnSpec,
+    pattern: Option<Regex>,
+}
+
+#[derive(Debug)]
+pub struct Schema {
+    columns: Vec<Column>,
+    allow_extra_columns: bool,
+}
+
+impl Schema {
+    pub fn load(path: impl AsRef<Path>) -> Result<Schema, SchemaError> {
+        Schema::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Schema, SchemaError> {
+        let spec: SchemaSpec = serde_json::from_str(json)?;
+        let mut columns: Vec<Column> = Vec::with_capacity(spec.columns.len());
+
+        for column in spec.columns {
+            let invalid = |message: &str| SchemaError::Column {
+                column: column.name.clone(),
+                message: message.to_string(),
+            };
+            if columns.iter().any(|c| c.spec.name == column.name) {
+                return Err(invalid("declared twice"));
+            }
+            let numeric = matches!(column.ty, ColumnType::Integer | ColumnType::Float);
+            if !numeric && (column.min.is_some() || column.max.is_some()) {
+                return Err(invalid("min and max only apply to integer and float columns"));
+            }
+            if let (Some(min), Some(max)) = (column.min, column.max)
+                && min > max
+            {
+                return Err(invalid("min is greater than max"));
+            }
+
+            let pattern = match &column.pattern {
+                Some(pattern) => Some(Regex::new(pattern).map_err(|source| SchemaError::Pattern {
+                    column: column.name.clone(),
+                    source,
+                })?),
+                None => None,
+            };
+            columns.push(Column {
+                spec: column,
+                pattern,
+            });
+        }
+
+        Ok(Schema {
+            columns,
+            allow_extra_columns: spec.allow_extra_columns,
+        })
+    }
+}
+
+// -----------------------------
+// Report
+// -----------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rule {
+    /// The record could not be read at all (quoting, UTF-8, field count).
+    Syntax,
+    MissingColumn,
+    UnknownColumn,
+    Required,
+    Type,
+    Pattern,
+    Range,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Violation {
+    pub line: Option<usize>,
+    pub column: Option<String>,
+    pub rule: Rule,
+    pub value: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Report {
+    /// Data records, header excluded, including unreadable ones.
+    pub records: usize,
+    pub invalid_records: usize,
+    pub violations: Vec<Violation>,
+}
+
+impl Report {
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Read errors already say where they happened.
+        if self.rule == Rule::Syntax {
+            return write!(f, "{}", self.message);
+        }
+        match (self.line, &self.column) {
+            (Some(line), Some(column)) => write!(f, "line {}, column '{}': ", line, column)?,
+            (Some(line), None) => write!(f, "line {}: ", line)?,
+            (None, Some(column)) => write!(f, "column '{}': ", column)?,
+            (None, None) => {}
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} records checked, {} invalid, {} violations",
+            self.records,
+            self.invalid_records,
+            self.violations.len()
+        )?;
+        for violation in &self.violations {
+            writeln!(f, "  {}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+// -----------------------------
+// Validation
+// -----------------------------
+/// Checks every record against the schema and reports all violations. The
+/// first record is the header; columns are matched by name. Unreadable
+/// records are reported and skipped, reading only stops on I/O errors.
+pub fn validate<R: BufRead>(schema: &Schema, mut reader: Reader<R>) -> Report {
+    let mut report = Report::default();
+
+    let (header_line, header): (Option<usize>, Vec<String>) = match reader.next_borrowed() {
+        Some(Ok(header)) => (Some(header.line()), header.iter().map(Cow::into_owned).collect()),
+        Some(Err(err)) => {
+            report.violations.push(syntax(err));
+            return report;
+        }
+        None => (None, Vec::new()),
+    };
+
+    // Where each schema column sits in the file, if it is there at all.
+    let positions: Vec<Option<usize>> = schema
+        .columns
+        .iter()
+        .map(|column| header.iter().position(|name| *name == column.spec.name))
+        .collect();
+
+    for (column, position) in schema.columns.iter().zip(&positions) {
+        if position.is_none() && column.spec.required {
+            report.violations.push(Violation {
+                line: header_line,
+                column: Some(column.spec.name.clone()),
+                rule: Rule::MissingColumn,
+                value: None,
+                message: "required column is missing".to_string(),
+            });
+        }
+    }
+    if !schema.allow_extra_columns {
+        for name in &header {
+            if !schema.columns.iter().any(|column| column.spec.name == *name) {
+                report.violations.push(Violation {
+                    line: header_line,
+                    column: Some(name.clone()),
+                    rule: Rule::UnknownColumn,
+                    value: None,
+                    message: "column is not in the schema".to_string(),
+                });
+            }
+        }
+    }
+
+    while let Some(record) = reader.next_borrowed() {
+        report.records += 1;
+        let before = report.violations.len();
+
+        match record {
+            Ok(record) => {
+                for (column, position) in schema.columns.iter().zip(&positions) {
+                    let Some(position) = position else { continue };
+                    let value = record.get(*position).unwrap_or_default();
+                    if let Some((rule, message)) = check(column, &value) {
+                        report.violations.push(Violation {
+                            line: Some(record.line()),
+                            column: Some(column.spec.name.clone()),
+                            rule,
+                            value: Some(value.into_owned()),
+                            message,
+                        });
+                    }
+                }
+            }
+            Err(err) => report.violations.push(syntax(err)),
+        }
+
+        if report.violations.len() > before {
+            report.invalid_records += 1;
+        }
+    }
+
+    report
+}
+
+fn syntax(err: CsvError) -> Violation {
+    Violation {
+        line: err.line(),
+        column: None,
+        rule: Rule::Syntax,
+        value: None,
+        message: err.to_string(),
+    }
+}
+
+// The first rule the cell breaks, if any.
+fn check(column: &Column, value: &str) -> Option<(Rule, String)> {
+    let spec = &column.spec;
+    if value.is_empty() {
+        return spec
+            .required
+            .then(|| (Rule::Required, "value is required".to_string()));
+    }
+
+    // Numbers may be padded with spaces, as for typed records.
+    let number = match spec.ty {
+        ColumnType::String => None,
+        ColumnType::Integer => match value.trim().parse::<i64>() {
+            Ok(n) => Some(n as f64),
+            Err(_) => return Some(not_a(value, spec.ty)),
+        },
+        ColumnType::Float => match value.trim().parse::<f64>() {
+            Ok(n) if n.is_finite() => Some(n),
+            _ => return Some(not_a(value, spec.ty)),
+        },
+        ColumnType::Bool => match value.trim().to_ascii_lowercase().as_str() {
+            "true" | "yes" | "1" | "false" | "no" | "0" => None,
+            _ => return Some(not_a(value, spec.ty)),
+        },
+        ColumnType::Date => match NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d") {
+            Ok(_) => None,
+            Err(_) => return Some(not_a(value, spec.ty)),
+        },
+    };
+
+    if let Some(pattern) = &column.pattern
+        && !pattern.is_match(value)
+    {
+        return Some((
+            Rule::Pattern,
+            format!("{:?} does not match /{}/", value, pattern.as_str()),
+        ));
+    }
+
+    if let Some(n) = number {
+        if let Some(min) = spec.min
+            && n < min
+        {
+            return Some((Rule::Range, format!("{} is below the minimum {}", value.trim(), min)));
+        }
+        if let Some(max) = spec.max
+            && n > max
+        {
+            return Some((Rule::Range, format!("{} is above the maximum {}", value.trim(), max)));
+        }
+    }
+
+    None
+}
+
+fn not_a(value: &str, ty: ColumnType) -> (Rule, String) {
+    (Rule::Type, format!("{:?} is not a valid {}", value, ty))
+}
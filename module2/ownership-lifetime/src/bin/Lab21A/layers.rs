@@ -0,0 +1,185 @@
+// src/bin/Lab21A/layers.rs
+// Layered configuration loading
+//
+// Later layers win:
+//   1. defaults (`AppConfig::default()`)
+//   2. a config file, JSON, TOML or YAML by extension
+//   3. APP_* environment variables, e.g. APP_MAX_CONNECTIONS=50
+//   4. command-line overrides, e.g. --set debug=true
+
+use crate::AppConfig;
+use anyhow::{bail, Context, Result};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const ENV_PREFIX: &str = "APP_";
+
+// -------------------------------
+// Sources
+// -------------------------------
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Default,
+    File(PathBuf),
+    Env(String),
+    CommandLine,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Default => write!(f, "default"),
+            Source::File(path) => write!(f, "file {}", path.display()),
+            Source::Env(var) => write!(f, "env {}", var),
+            Source::CommandLine => write!(f, "command line"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct LoadedConfig {
+    pub config: AppConfig,
+    /// Where each setting's final value came from, by setting name.
+    pub sources: BTreeMap<String, Source>,
+}
+
+// -------------------------------
+// Loader
+// -------------------------------
+#[derive(Debug, Default)]
+pub struct ConfigLoader {
+    file: Option<PathBuf>,
+    env: Vec<(String, String)>,
+    overrides: Vec<(String, String)>,
+}
+
+impl ConfigLoader {
+    pub fn new() -> Self {
+        ConfigLoader::default()
+    }
+
+    pub fn file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.file = Some(path.into());
+        self
+    }
+
+    /// Usually `std::env::vars()`; only `APP_*` variables are kept.
+    pub fn env(mut self, vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        self.env = vars
+            .into_iter()
+            .filter(|(name, _)| name.starts_with(ENV_PREFIX))
+            .collect();
+        self
+    }
+
+    pub fn set(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.overrides.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn load(&self) -> Result<LoadedConfig> {
+        let Value::Object(defaults) = serde_json::to_value(AppConfig::default())? else {
+            bail!("AppConfig must serialize to an object");
+        };
+        let mut layers = Layers {
+            sources: defaults.keys().map(|key| (key.clone(), Source::Default)).collect(),
+            values: defaults,
+        };
+
+        if let Some(path) = &self.file {
+            for (key, value) in read_file(path)? {
+                if !layers.values.contains_key(&key) {
+                    bail!("{}: unknown setting '{}'", path.display(), key);
+                }
+                layers.apply(&key, value, Source::File(path.clone()))?;
+            }
+        }
+
+        // Other programs use APP_* too, so unknown variables are skipped.
+        for (var, raw) in &self.env {
+            let key = var[ENV_PREFIX.len()..].to_ascii_lowercase();
+            if layers.values.contains_key(&key) {
+                layers.apply_str(&key, raw, Source::Env(var.clone()))?;
+            }
+        }
+
+        for (key, raw) in &self.overrides {
+            if !layers.values.contains_key(key) {
+                let known: Vec<&str> = layers.values.keys().map(String::as_str).collect();
+                bail!("Unknown setting '{}' (known: {})", key, known.join(", "));
+            }
+            layers.apply_str(key, raw, Source::CommandLine)?;
+        }
+
+        Ok(LoadedConfig {
+            config: serde_json::from_value(Value::Object(layers.values))?,
+            sources: layers.sources,
+        })
+    }
+}
+
+struct Layers {
+    values: Map<String, Value>,
+    sources: BTreeMap<String, Source>,
+}
+
+impl Layers {
+    // Checked right away, so a bad value is blamed on the layer it came from.
+    fn apply(&mut self, key: &str, value: Value, source: Source) -> Result<()> {
+        let mut merged = self.values.clone();
+        merged.insert(key.to_string(), value);
+        serde_json::from_value::<AppConfig>(Value::Object(merged.clone()))
+            .with_context(|| format!("Invalid {} from {}", key, source))?;
+
+        self.values = merged;
+        self.sources.insert(key.to_string(), source);
+        Ok(())
+    }
+
+    // Environment and command-line values are text; they take the type of
+    // the setting they replace.
+    fn apply_str(&mut self, key: &str, raw: &str, source: Source) -> Result<()> {
+        let value = match &self.values[key] {
+            Value::Bool(_) => match raw.trim().to_ascii_lowercase().as_str() {
+                "true" | "yes" | "on" | "1" => Value::Bool(true),
+                "false" | "no" | "off" | "0" => Value::Bool(false),
+                _ => bail!("Invalid {} from {}: expected true or false, got '{}'", key, source, raw),
+            },
+            Value::Number(_) => match raw.trim().parse::<i64>() {
+                Ok(n) => Value::from(n),
+                Err(_) => bail!("Invalid {} from {}: expected a number, got '{}'", key, source, raw),
+            },
+            _ => Value::String(raw.to_string()),
+        };
+        self.apply(key, value, source)
+    }
+}
+
+fn read_file(path: &Path) -> Result<Map<String, Value>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase);
+    let value: Value = match extension.as_deref() {
+        Some("json") => serde_json::from_str(&content).context("Failed to parse JSON config")?,
+        Some("toml") => toml::from_str(&content).context("Failed to parse TOML config")?,
+        Some("yaml" | "yml") => serde_yaml::from_str(&content).context("Failed to parse YAML config")?,
+        _ => bail!(
+            "Unsupported config file: {} (use .json, .toml, .yaml or .yml)",
+            path.display()
+        ),
+    };
+
+    match value {
+        Value::Object(settings) => Ok(settings),
+        // An empty YAML file.
+        Value::Null => Ok(Map::new()),
+        _ => bail!("{}: expected a table of settings", path.display()),
+    }
+}
@@ -1,7 +1,15 @@
 // Lab 21 – File I/O + Serialization (Serde)
+//
+// Usage: Lab21A [--config file.json|.toml|.yaml] [--set key=value]...
+// The last section loads AppConfig from defaults, the config file, APP_*
+// environment variables and --set overrides (see layers.rs).
 
-use anyhow::{Context, Result};
+mod layers;
+
+use anyhow::{bail, Context, Result};
+use layers::ConfigLoader;
 use serde::{Deserialize, Serialize};
+use std::env;
 use std::fs::{self, File};
 use std::io::Write;
 
@@ -16,6 +24,17 @@ struct AppConfig {
     max_connections: u32,
 }
 
+impl Default for AppConfig {
+    fn default() -> Self {
+        AppConfig {
+            app_name: "RustServer".to_string(),
+            version: "1.0.0".to_string(),
+            debug: false,
+            max_connections: 100,
+        }
+    }
+}
+
 // -------------------------------
 // Main
 // -------------------------------
@@ -45,9 +64,48 @@ fn main() -> Result<()> {
     let loaded_csv = load_config_csv("config.csv")?;
     println!("Loaded from CSV: {:?}\n", loaded_csv);
 
+    // -------------------------------
+    // Layered Loading
+    // -------------------------------
+    println!("--- Layered Configuration ---");
+    let loaded = loader_from_args(env::args().skip(1))?
+        .env(env::vars())
+        .load()?;
+    let values = serde_json::to_value(&loaded.config)?;
+    for (key, source) in &loaded.sources {
+        println!("  {:<16} {:<14} ({})", key, values[key.as_str()].to_string(), source);
+    }
+    println!("Loaded from layers: {:?}\n", loaded.config);
+
     Ok(())
 }
 
+// -------------------------------
+// Command Line
+// -------------------------------
+fn loader_from_args(mut args: impl Iterator<Item = String>) -> Result<ConfigLoader> {
+    let mut loader = ConfigLoader::new();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => {
+                let path = args.next().context("--config needs a path")?;
+                loader = loader.file(path);
+            }
+            "--set" => {
+                let setting = args.next().context("--set needs key=value")?;
+                let Some((key, value)) = setting.split_once('=') else {
+                    bail!("--set expects key=value, got '{}'", setting);
+                };
+                loader = loader.set(key.trim(), value);
+            }
+            other => bail!("Unknown argument '{}'", other),
+        }
+    }
+
+    Ok(loader)
+}
+
 // -------------------------------
 // JSON: Save
 // -------------------------------
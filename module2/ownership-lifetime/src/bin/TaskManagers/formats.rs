@@ -0,0 +1,536 @@
+use crate::model::{Priority, Task};
+use crate::recurrence::Recurrence;
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::str::FromStr;
+
+// -----------------------------
+// Exchange Formats
+// -----------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    Markdown,
+    TodoTxt,
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(Format::Csv),
+            "md" | "markdown" => Ok(Format::Markdown),
+            "todotxt" | "todo.txt" | "todo" => Ok(Format::TodoTxt),
+            other => anyhow::bail!("Unknown format '{}' (use csv, md or todotxt)", other),
+        }
+    }
+}
+
+impl Format {
+    /// Guesses the format from a file extension (`.csv`, `.md`, `.txt`).
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "csv" => Some(Format::Csv),
+            "md" | "markdown" => Some(Format::Markdown),
+            "txt" => Some(Format::TodoTxt),
+            _ => None,
+        }
+    }
+}
+
+// A task read from another tool; the id is kept only if the file had one.
+#[derive(Debug, Clone)]
+pub struct ImportedTask {
+    pub id: Option<u32>,
+    pub title: String,
+    pub completed: bool,
+    pub priority: Option<Priority>,
+    pub due: Option<NaiveDate>,
+    pub tags: Vec<String>,
+    pub recur: Option<Recurrence>,
+    pub parent: Option<u32>,
+    pub blocked_by: Vec<u32>,
+    pub notes: Option<String>,
+}
+
+impl ImportedTask {
+    pub fn into_task(self, id: u32) -> Task {
+        Task {
+            id,
+            title: self.title,
+            completed: self.completed,
+            priority: self.priority,
+            due: self.due,
+            tags: self.tags,
+            recur: self.recur,
+            parent: self.parent,
+            blocked_by: self.blocked_by,
+            notes: self.notes,
+            time_log: Vec::new(),
+        }
+    }
+}
+
+pub fn export(tasks: &[Task], format: Format) -> Result<String> {
+    match format {
+        Format::Csv => export_csv(tasks),
+        Format::Markdown => Ok(tasks.iter().map(|t| markdown_line(t) + "\n").collect()),
+        Format::TodoTxt => Ok(tasks.iter().map(|t| todotxt_line(t) + "\n").collect()),
+    }
+}
+
+pub fn import(content: &str, format: Format) -> Result<Vec<ImportedTask>> {
+    let tasks = match format {
+        Format::Csv => import_csv(content)?,
+        Format::Markdown => parse_lines(content, parse_markdown_line)?,
+        Format::TodoTxt => parse_lines(content, parse_todotxt_line)?,
+    };
+
+    if let Some(pos) = tasks.iter().position(|t| t.title.trim().is_empty()) {
+        anyhow::bail!("Task {} in the import has an empty title", pos + 1);
+    }
+
+    Ok(tasks)
+}
+
+// -----------------------------
+// CSV
+// -----------------------------
+// Flat row: tags and blocker ids are space separated.
+#[derive(Debug, Serialize, Deserialize)]
+struct CsvRow {
+    #[serde(default)]
+    id: Option<u32>,
+    title: String,
+    #[serde(default)]
+    completed: bool,
+    #[serde(default)]
+    priority: Option<Priority>,
+    #[serde(default)]
+    due: Option<NaiveDate>,
+    #[serde(default)]
+    tags: String,
+    #[serde(default)]
+    recur: Option<Recurrence>,
+    #[serde(default)]
+    parent: Option<u32>,
+    #[serde(default)]
+    blocked_by: String,
+    #[serde(default)]
+    notes: Option<String>,
+}
+
+fn export_csv(tasks: &[Task]) -> Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+
+    for t in tasks {
+        writer.serialize(CsvRow {
+            id: Some(t.id),
+            title: t.title.clone(),
+            completed: t.completed,
+            priority: t.priority,
+            due: t.due,
+            tags: t.tags.join(" "),
+            recur: t.recur.clone(),
+            parent: t.parent,
+            blocked_by: join_ids(&t.blocked_by, " "),
+            notes: t.notes.clone(),
+        })?;
+    }
+
+    let bytes = writer.into_inner().context("Failed to finish CSV output")?;
+    Ok(String::from_utf8(bytes)?)
+}
+
+fn import_csv(content: &str) -> Result<Vec<ImportedTask>> {
+    let mut reader = csv::Reader::from_reader(content.as_bytes());
+    let mut tasks = Vec::new();
+
+    for (i, row) in reader.deserialize::<CsvRow>().enumerate() {
+        // Header is line 1.
+        let row = row.with_context(|| format!("Invalid CSV record on line {}", i + 2))?;
+        tasks.push(ImportedTask {
+            id: row.id,
+            title: row.title.trim().to_string(),
+            completed: row.completed,
+            priority: row.priority,
+            due: row.due,
+            tags: row.tags.split_whitespace().map(str::to_lowercase).collect(),
+            recur: row.recur,
+            parent: row.parent,
+            blocked_by: parse_ids(&row.blocked_by, ' ')
+                .with_context(|| format!("Invalid blocked_by on line {}", i + 2))?,
+            notes: row.notes.filter(|n| !n.trim().is_empty()),
+        });
+    }
+
+    Ok(tasks)
+}
+
+// -----------------------------
+// Markdown Checklist
+// -----------------------------
+// - [x] Write report #work due:2026-11-01 priority:high rec:weekly parent:1 blocked:2,4 note:Ask%20Bob id:3
+fn markdown_line(t: &Task) -> String {
+    let completed = if t.completed { "x" } else { " " };
+    let mut line = format!("- [{}] {}", completed, escape_title(&t.title, '#'));
+    for tag in &t.tags {
+        line.push_str(&format!(" #{}", tag));
+    }
+    if let Some(due) = t.due {
+        line.push_str(&format!(" due:{}", due));
+    }
+    if let Some(priority) = t.priority {
+        line.push_str(&format!(" priority:{}", priority));
+    }
+    if let Some(recur) = &t.recur {
+        line.push_str(&format!(" {}", recur_token(recur)));
+    }
+    for token in link_tokens(t) {
+        line.push(' ');
+        line.push_str(&token);
+    }
+    line.push_str(&format!(" id:{}", t.id));
+    line
+}
+
+// Lines that are not checklist items (headings, prose) are ignored.
+fn parse_markdown_line(line: &str) -> Result<Option<ImportedTask>> {
+    let line = line.trim_start();
+    let Some(rest) = line
+        .strip_prefix("- [")
+        .or_else(|| line.strip_prefix("* ["))
+    else {
+        return Ok(None);
+    };
+
+    let (completed, rest) = match rest.get(..2) {
+        Some(" ]") => (false, &rest[2..]),
+        Some("x]") | Some("X]") => (true, &rest[2..]),
+        _ => return Ok(None),
+    };
+
+    let mut task = empty_import(completed);
+    let mut words = Vec::new();
+
+    for word in rest.split_whitespace() {
+        if let Some(word) = word.strip_prefix('\\') {
+            words.push(word);
+        } else if let Some(tag) = word.strip_prefix('#').filter(|t| !t.is_empty()) {
+            task.tags.push(tag.to_lowercase());
+        } else if !parse_key_value(word, &mut task)? {
+            words.push(word);
+        }
+    }
+
+    task.title = words.join(" ");
+    Ok(Some(task))
+}
+
+// -----------------------------
+// todo.txt
+// -----------------------------
+// x (A) Write report +work due:2026-11-01 rec:every_3_days parent:1 blocked:2 note:Ask%20Bob id:3
+// Priorities map to A = high, B = medium, C = low.
+fn todotxt_line(t: &Task) -> String {
+    let mut parts = Vec::new();
+    if t.completed {
+        parts.push("x".to_string());
+    }
+    if let Some(priority) = t.priority {
+        parts.push(format!("({})", todotxt_priority(priority)));
+    }
+    let mut title = escape_title(&t.title, '+');
+    // The first title word must not pass for a completion mark, a date or
+    // a priority either.
+    let first = title.split(' ').next().unwrap_or_default();
+    if first == "x" || NaiveDate::parse_from_str(first, "%Y-%m-%d").is_ok() || is_todotxt_priority(first) {
+        title.insert(0, '\\');
+    }
+    parts.push(title);
+    parts.extend(t.tags.iter().map(|tag| format!("+{}", tag)));
+    if let Some(due) = t.due {
+        parts.push(format!("due:{}", due));
+    }
+    if let Some(recur) = &t.recur {
+        parts.push(recur_token(recur));
+    }
+    parts.extend(link_tokens(t));
+    parts.push(format!("id:{}", t.id));
+    parts.join(" ")
+}
+
+fn todotxt_priority(priority: Priority) -> char {
+    match priority {
+        Priority::High => 'A',
+        Priority::Medium => 'B',
+        Priority::Low => 'C',
+    }
+}
+
+// `(A)` to `(Z)`
+fn is_todotxt_priority(word: &str) -> bool {
+    let bytes = word.as_bytes();
+    bytes.len() == 3 && bytes[0] == b'(' && bytes[2] == b')' && bytes[1].is_ascii_uppercase()
+}
+
+fn parse_todotxt_line(line: &str) -> Result<Option<ImportedTask>> {
+    let mut words = line.split_whitespace().peekable();
+    if words.peek().is_none() {
+        return Ok(None);
+    }
+
+    let completed = words.next_if_eq(&"x").is_some();
+    let mut task = empty_import(completed);
+
+    // Completion and creation dates are not tracked, so skip them.
+    while words
+        .peek()
+        .is_some_and(|w| NaiveDate::parse_from_str(w, "%Y-%m-%d").is_ok())
+    {
+        words.next();
+    }
+
+    if let Some(word) = words.next_if(|word| is_todotxt_priority(word)) {
+        task.priority = Some(match word.as_bytes()[1] {
+            b'A' => Priority::High,
+            b'B' => Priority::Medium,
+            _ => Priority::Low,
+        });
+    }
+
+    let mut title = Vec::new();
+    for word in words {
+        if let Some(word) = word.strip_prefix('\\') {
+            title.push(word);
+        } else if let Some(tag) = word.strip_prefix('+').filter(|t| !t.is_empty()) {
+            task.tags.push(tag.to_lowercase());
+        } else if !parse_key_value(word, &mut task)? {
+            title.push(word);
+        }
+    }
+
+    task.title = title.join(" ");
+    Ok(Some(task))
+}
+
+// -----------------------------
+// Shared Helpers
+// -----------------------------
+fn empty_import(completed: bool) -> ImportedTask {
+    ImportedTask {
+        id: None,
+        title: String::new(),
+        completed,
+        priority: None,
+        due: None,
+        tags: Vec::new(),
+        recur: None,
+        parent: None,
+        blocked_by: Vec::new(),
+        notes: None,
+    }
+}
+
+// Parent, blockers and notes: the tokens both line formats end with.
+fn link_tokens(t: &Task) -> Vec<String> {
+    let mut tokens = Vec::new();
+    if let Some(parent) = t.parent {
+        tokens.push(format!("parent:{}", parent));
+    }
+    if !t.blocked_by.is_empty() {
+        tokens.push(format!("blocked:{}", join_ids(&t.blocked_by, ",")));
+    }
+    if let Some(notes) = &t.notes {
+        tokens.push(format!("note:{}", escape_note(notes)));
+    }
+    tokens
+}
+
+// Notes must fit in one word: whitespace and `%` are written as `%XX`.
+fn escape_note(notes: &str) -> String {
+    let mut escaped = String::with_capacity(notes.len());
+    for c in notes.chars() {
+        match c {
+            '%' | ' ' | '\t' | '\n' | '\r' => escaped.push_str(&format!("%{:02X}", c as u8)),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape_note(word: &str) -> Result<String> {
+    let invalid = || anyhow::anyhow!("Invalid escape in note '{}'", word);
+    let mut bytes = Vec::with_capacity(word.len());
+    let mut rest = word.as_bytes();
+    while let Some((&b, after)) = rest.split_first() {
+        if b == b'%' {
+            let hex = after.get(..2).ok_or_else(invalid)?;
+            let hex = std::str::from_utf8(hex).map_err(|_| invalid())?;
+            bytes.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+            rest = &after[2..];
+        } else {
+            bytes.push(b);
+            rest = after;
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
+
+fn join_ids(ids: &[u32], sep: &str) -> String {
+    ids.iter().map(u32::to_string).collect::<Vec<_>>().join(sep)
+}
+
+fn parse_ids(text: &str, sep: char) -> Result<Vec<u32>> {
+    text.split(sep)
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(|id| id.parse().with_context(|| format!("Invalid task id '{}'", id)))
+        .collect()
+}
+
+// Line formats are split on whitespace, so spaces in the rule become `_`.
+fn recur_token(recur: &Recurrence) -> String {
+    format!("rec:{}", recur.to_string().replace(' ', "_"))
+}
+
+const KEYS: [&str; 7] = ["id", "due", "priority", "rec", "parent", "blocked", "note"];
+
+// Title words that would read back as a tag or a `key:value` field get a
+// leading `\`, and so do words that already start with one; import drops
+// it again.
+fn escape_title(title: &str, tag_prefix: char) -> String {
+    title
+        .split(' ')
+        .map(|word| {
+            let reserved = word.starts_with('\\')
+                || word.strip_prefix(tag_prefix).is_some_and(|tag| !tag.is_empty())
+                || word.split_once(':').is_some_and(|(key, _)| KEYS.contains(&key));
+            if reserved {
+                format!("\\{}", word)
+            } else {
+                word.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Recognises `id:`, `due:`, `priority:`, `rec:`, `parent:`, `blocked:` and
+// `note:`; anything else is part of the title.
+fn parse_key_value(word: &str, task: &mut ImportedTask) -> Result<bool> {
+    let Some((key, value)) = word.split_once(':') else {
+        return Ok(false);
+    };
+
+    match key {
+        "id" => task.id = Some(value.parse().with_context(|| format!("Invalid id '{}'", value))?),
+        "due" => {
+            task.due = Some(
+                NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                    .with_context(|| format!("Invalid due date '{}'", value))?,
+            )
+        }
+        "priority" => task.priority = Some(value.parse()?),
+        "rec" => task.recur = Some(value.replace('_', " ").parse()?),
+        "parent" => {
+            task.parent = Some(value.parse().with_context(|| format!("Invalid parent '{}'", value))?)
+        }
+        "blocked" => task.blocked_by = parse_ids(value, ',')?,
+        "note" => task.notes = Some(unescape_note(value)?),
+        _ => return Ok(false),
+    }
+
+    Ok(true)
+}
+
+fn parse_lines(
+    content: &str,
+    parse: fn(&str) -> Result<Option<ImportedTask>>,
+) -> Result<Vec<ImportedTask>> {
+    let mut tasks = Vec::new();
+
+    for (i, line) in content.lines().enumerate() {
+        let parsed = parse(line).with_context(|| format!("Invalid task on line {}", i + 1))?;
+        tasks.extend(parsed);
+    }
+
+    Ok(tasks)
+}
+
+// -----------------------------
+// Tests
+// -----------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: u32, title: &str) -> Task {
+        ImportedTask {
+            title: title.to_string(),
+            ..empty_import(false)
+        }
+        .into_task(id)
+    }
+
+    // Exports `tasks` and reads them back with the ids from the file.
+    fn round_trip(tasks: &[Task], format: Format) -> Vec<Task> {
+        let content = export(tasks, format).unwrap();
+        import(&content, format)
+            .unwrap()
+            .into_iter()
+            .map(|t| {
+                let id = t.id.expect("exports carry ids");
+                t.into_task(id)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn notes_survive_line_formats() {
+        let mut t = task(1, "Write report");
+        t.notes = Some("Ask Bob: 50% done\nthen\tsend".to_string());
+        t.tags = vec!["work".to_string()];
+        t.priority = Some(Priority::High);
+        t.blocked_by = vec![2];
+        let tasks = vec![t, task(2, "Collect numbers")];
+
+        for format in [Format::Markdown, Format::TodoTxt, Format::Csv] {
+            assert_eq!(round_trip(&tasks, format), tasks, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn titles_that_look_like_fields_survive() {
+        let titles = [
+            "Read chapter 3 due:Friday",
+            "Fix bug #42",
+            "Add +1 button",
+            "id:7 priority:urgent note:x",
+            "x marks the spot",
+            "2026-01-01 retrospective",
+            "(A) team first",
+            r"C:\temp \#not-a-tag",
+        ];
+        let tasks: Vec<Task> = (1..).zip(titles).map(|(id, title)| task(id, title)).collect();
+
+        for format in [Format::Markdown, Format::TodoTxt] {
+            assert_eq!(round_trip(&tasks, format), tasks, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn escaped_words_stay_in_the_title() {
+        let imported = import("- [ ] Fix bug \\#42 #bugs", Format::Markdown).unwrap();
+        assert_eq!(imported[0].title, "Fix bug #42");
+        assert_eq!(imported[0].tags, ["bugs"]);
+    }
+
+    #[test]
+    fn bad_note_escape_is_an_error() {
+        assert!(import("- [ ] Write note:100%", Format::Markdown).is_err());
+    }
+}
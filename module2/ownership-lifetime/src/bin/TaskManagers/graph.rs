@@ -0,0 +1,215 @@
+use crate::model::Task;
+use anyhow::Result;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+// -----------------------------
+// Task Graph
+// -----------------------------
+// A task depends on everything in its `blocked_by` list and on its own
+// subtasks, so a parent is only ready once its children are done. Both kinds
+// of edge live in one graph, which must stay acyclic. References to ids that
+// no longer exist are ignored.
+pub struct TaskGraph<'a> {
+    tasks: HashMap<u32, &'a Task>,
+    children: HashMap<u32, Vec<u32>>,
+}
+
+impl<'a> TaskGraph<'a> {
+    pub fn new(tasks: &'a [Task]) -> Self {
+        let by_id: HashMap<u32, &Task> = tasks.iter().map(|t| (t.id, t)).collect();
+        let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+
+        for t in tasks {
+            if let Some(parent) = t.parent.filter(|p| by_id.contains_key(p)) {
+                children.entry(parent).or_default().push(t.id);
+            }
+        }
+        for list in children.values_mut() {
+            list.sort_unstable();
+        }
+
+        TaskGraph {
+            tasks: by_id,
+            children,
+        }
+    }
+
+    fn children(&self, id: u32) -> &[u32] {
+        self.children.get(&id).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Ids `id` has to wait for, known tasks only.
+    pub fn dependencies(&self, id: u32) -> Vec<u32> {
+        let Some(task) = self.tasks.get(&id) else {
+            return Vec::new();
+        };
+
+        let deps: BTreeSet<u32> = task
+            .blocked_by
+            .iter()
+            .chain(self.children(id))
+            .copied()
+            .filter(|dep| self.tasks.contains_key(dep))
+            .collect();
+        deps.into_iter().collect()
+    }
+
+    /// Incomplete dependencies of `id`.
+    pub fn pending(&self, id: u32) -> Vec<u32> {
+        self.dependencies(id)
+            .into_iter()
+            .filter(|dep| !self.tasks[dep].completed)
+            .collect()
+    }
+
+    /// Fails with the ids along the loop if the graph has a cycle.
+    pub fn check_acyclic(&self) -> Result<()> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Mark {
+            Visiting,
+            Done,
+        }
+
+        fn visit(
+            graph: &TaskGraph<'_>,
+            id: u32,
+            marks: &mut HashMap<u32, Mark>,
+            path: &mut Vec<u32>,
+        ) -> Result<()> {
+            match marks.get(&id) {
+                Some(Mark::Done) => return Ok(()),
+                Some(Mark::Visiting) => {
+                    let start = path.iter().position(|&p| p == id).unwrap_or(0);
+                    let cycle: Vec<String> = path[start..]
+                        .iter()
+                        .chain(std::iter::once(&id))
+                        .map(|id| format!("#{}", id))
+                        .collect();
+                    anyhow::bail!("Dependency cycle: {}", cycle.join(" -> "));
+                }
+                None => {}
+            }
+
+            marks.insert(id, Mark::Visiting);
+            path.push(id);
+            for dep in graph.dependencies(id) {
+                visit(graph, dep, marks, path)?;
+            }
+            path.pop();
+            marks.insert(id, Mark::Done);
+            Ok(())
+        }
+
+        let mut ids: Vec<u32> = self.tasks.keys().copied().collect();
+        ids.sort_unstable();
+
+        let mut marks = HashMap::new();
+        for id in ids {
+            visit(self, id, &mut marks, &mut Vec::new())?;
+        }
+        Ok(())
+    }
+
+    /// Incomplete tasks in an order that respects every dependency (Kahn's
+    /// algorithm). Among tasks that are free at the same time, higher
+    /// priority, then earlier due date, then lower id goes first.
+    pub fn topological_order(&self) -> Vec<&'a Task> {
+        let open: HashSet<u32> = self
+            .tasks
+            .values()
+            .filter(|t| !t.completed)
+            .map(|t| t.id)
+            .collect();
+
+        let mut waiting: HashMap<u32, usize> = HashMap::new();
+        let mut dependents: HashMap<u32, Vec<u32>> = HashMap::new();
+        for &id in &open {
+            let pending = self.pending(id);
+            waiting.insert(id, pending.len());
+            for dep in pending {
+                dependents.entry(dep).or_default().push(id);
+            }
+        }
+
+        let rank = |id: &u32| {
+            let t = self.tasks[id];
+            (Reverse(t.priority), t.due.is_none(), t.due, t.id)
+        };
+        let mut ready: Vec<u32> = waiting
+            .iter()
+            .filter(|(_, n)| **n == 0)
+            .map(|(id, _)| *id)
+            .collect();
+        let mut order = Vec::new();
+
+        while !ready.is_empty() {
+            ready.sort_by_key(rank);
+            let id = ready.remove(0);
+            order.push(self.tasks[&id]);
+
+            for dependent in dependents.remove(&id).unwrap_or_default() {
+                let n = waiting.get_mut(&dependent).expect("dependent is open");
+                *n -= 1;
+                if *n == 0 {
+                    ready.push(dependent);
+                }
+            }
+        }
+
+        order
+    }
+
+    /// Top-level tasks first, each followed by its subtasks.
+    pub fn render_tree(&self) -> Vec<String> {
+        let mut roots: Vec<u32> = self
+            .tasks
+            .values()
+            .filter(|t| t.parent.is_none_or(|p| !self.tasks.contains_key(&p)))
+            .map(|t| t.id)
+            .collect();
+        roots.sort_unstable();
+
+        let mut lines = Vec::new();
+        for id in roots {
+            self.render_node(id, "", None, &mut lines);
+        }
+        lines
+    }
+
+    fn render_node(&self, id: u32, prefix: &str, last: Option<bool>, lines: &mut Vec<String>) {
+        let t = self.tasks[&id];
+        let status = if t.completed { "✔" } else { " " };
+        let branch = match last {
+            None => "",
+            Some(true) => "└── ",
+            Some(false) => "├── ",
+        };
+        let blocked: Vec<String> = t
+            .blocked_by
+            .iter()
+            .filter(|dep| self.tasks.contains_key(dep))
+            .map(|dep| format!("#{}", dep))
+            .collect();
+        let blocked = if blocked.is_empty() {
+            String::new()
+        } else {
+            format!("  (blocked by {})", blocked.join(", "))
+        };
+
+        lines.push(format!(
+            "{}{}[{}] #{} {}{}",
+            prefix, branch, status, t.id, t.title, blocked
+        ));
+
+        let child_prefix = match last {
+            None => prefix.to_string(),
+            Some(true) => format!("{}    ", prefix),
+            Some(false) => format!("{}│   ", prefix),
+        };
+        let children = self.children(id);
+        for (i, &child) in children.iter().enumerate() {
+            self.render_node(child, &child_prefix, Some(i + 1 == children.len()), lines);
+        }
+    }
+}
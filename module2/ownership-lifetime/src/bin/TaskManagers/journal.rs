@@ -0,0 +1,297 @@
+use crate::model::Task;
+use crate::store::{Edit, TaskStore};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// -----------------------------
+// Journal Entries
+// -----------------------------
+// One task before and after a command; `None` means it did not exist.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Change {
+    pub before: Option<Task>,
+    pub after: Option<Task>,
+}
+
+impl Change {
+    pub fn added(task: Task) -> Self {
+        Change {
+            before: None,
+            after: Some(task),
+        }
+    }
+
+    pub fn removed(task: Task) -> Self {
+        Change {
+            before: Some(task),
+            after: None,
+        }
+    }
+
+    pub fn updated(before: Task, after: Task) -> Self {
+        Change {
+            before: Some(before),
+            after: Some(after),
+        }
+    }
+
+    fn id(&self) -> u32 {
+        self.after
+            .as_ref()
+            .or(self.before.as_ref())
+            .map(|t| t.id)
+            .unwrap_or_default()
+    }
+
+    fn inverse(&self) -> Change {
+        Change {
+            before: self.after.clone(),
+            after: self.before.clone(),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match (&self.before, &self.after) {
+            (None, Some(t)) => format!("+#{} {}", t.id, t.title),
+            (Some(t), None) => format!("-#{} {}", t.id, t.title),
+            (_, Some(t)) => format!("~#{} {}", t.id, t.title),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum EntryKind {
+    Command { command: String, changes: Vec<Change> },
+    Undo { target: u64 },
+    Redo { target: u64 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub seq: u64,
+    pub at: DateTime<Local>,
+    #[serde(flatten)]
+    pub kind: EntryKind,
+}
+
+// -----------------------------
+// Journal File
+// -----------------------------
+// Append-only JSON lines stored as `<task file>.journal.jsonl`. Undo and redo are
+// entries too, so the file is a complete history and is never rewritten.
+pub struct Journal {
+    path: PathBuf,
+}
+
+// Which commands can currently be undone or redone, most recent last.
+#[derive(Debug, Default)]
+struct UndoState {
+    done: Vec<u64>,
+    undone: Vec<u64>,
+}
+
+impl Journal {
+    pub fn for_store(data_path: &Path) -> Self {
+        let mut path = data_path.as_os_str().to_owned();
+        path.push(".journal.jsonl");
+        Journal {
+            path: PathBuf::from(path),
+        }
+    }
+
+    pub fn entries(&self) -> Result<Vec<JournalEntry>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read {}", self.path.display()))?;
+
+        content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                serde_json::from_str(line).with_context(|| {
+                    format!("{} is corrupted at line {}", self.path.display(), i + 1)
+                })
+            })
+            .collect()
+    }
+
+    fn append(&self, kind: EntryKind) -> Result<JournalEntry> {
+        let seq = self.entries()?.last().map(|e| e.seq + 1).unwrap_or(1);
+        let entry = JournalEntry {
+            seq,
+            at: Local::now(),
+            kind,
+        };
+
+        let mut line = serde_json::to_string(&entry).context("Failed to serialize journal entry")?;
+        line.push('\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open {}", self.path.display()))?;
+        file.write_all(line.as_bytes())
+            .context("Failed to append to journal")?;
+        file.sync_all().context("Failed to sync journal")?;
+
+        Ok(entry)
+    }
+
+    /// Records a command that has already been applied to the store.
+    pub fn record(&self, command: &str, changes: Vec<Change>) -> Result<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        self.append(EntryKind::Command {
+            command: command.to_string(),
+            changes,
+        })?;
+        Ok(())
+    }
+
+    fn undo_state(entries: &[JournalEntry]) -> UndoState {
+        let mut state = UndoState::default();
+
+        for entry in entries {
+            match entry.kind {
+                EntryKind::Command { .. } => {
+                    state.done.push(entry.seq);
+                    state.undone.clear();
+                }
+                EntryKind::Undo { target } => {
+                    state.done.retain(|&seq| seq != target);
+                    state.undone.push(target);
+                }
+                EntryKind::Redo { target } => {
+                    state.undone.retain(|&seq| seq != target);
+                    state.done.push(target);
+                }
+            }
+        }
+
+        state
+    }
+
+    fn command_changes(entries: &[JournalEntry], seq: u64) -> Option<(&str, &[Change])> {
+        entries.iter().find(|e| e.seq == seq).and_then(|e| match &e.kind {
+            EntryKind::Command { command, changes } => Some((command.as_str(), changes.as_slice())),
+            _ => None,
+        })
+    }
+
+    /// Reverts the most recent command that is still in effect.
+    pub fn undo(&self, store: &mut dyn TaskStore) -> Result<Option<String>> {
+        let entries = self.entries()?;
+        let Some(&target) = Self::undo_state(&entries).done.last() else {
+            return Ok(None);
+        };
+        let (command, changes) =
+            Self::command_changes(&entries, target).context("Journal entry is missing")?;
+
+        let inverse: Vec<Change> = changes.iter().rev().map(Change::inverse).collect();
+        apply(store, &inverse)?;
+        self.append(EntryKind::Undo { target })?;
+
+        Ok(Some(summary(target, command, changes)))
+    }
+
+    /// Re-applies the most recently undone command.
+    pub fn redo(&self, store: &mut dyn TaskStore) -> Result<Option<String>> {
+        let entries = self.entries()?;
+        let Some(&target) = Self::undo_state(&entries).undone.last() else {
+            return Ok(None);
+        };
+        let (command, changes) =
+            Self::command_changes(&entries, target).context("Journal entry is missing")?;
+
+        apply(store, changes)?;
+        self.append(EntryKind::Redo { target })?;
+
+        Ok(Some(summary(target, command, changes)))
+    }
+
+    pub fn print_history(&self, limit: usize) -> Result<()> {
+        let entries = self.entries()?;
+
+        if entries.is_empty() {
+            println!("No history yet.");
+            return Ok(());
+        }
+
+        // Commands whose latest undo has not been redone, even once a newer
+        // command has made them impossible to redo.
+        let mut reverted = HashSet::new();
+        for entry in &entries {
+            match entry.kind {
+                EntryKind::Undo { target } => reverted.insert(target),
+                EntryKind::Redo { target } => reverted.remove(&target),
+                EntryKind::Command { .. } => false,
+            };
+        }
+
+        println!("Seq | When                | Entry");
+        println!("-------------------------------------------------------");
+
+        let skip = entries.len().saturating_sub(limit);
+        for entry in &entries[skip..] {
+            let when = entry.at.format("%Y-%m-%d %H:%M:%S");
+            let text = match &entry.kind {
+                EntryKind::Command { command, changes } => {
+                    let mark = if reverted.contains(&entry.seq) {
+                        " (undone)"
+                    } else {
+                        ""
+                    };
+                    format!("{}{}", summary(entry.seq, command, changes), mark)
+                }
+                EntryKind::Undo { target } => format!("undo of {}", target),
+                EntryKind::Redo { target } => format!("redo of {}", target),
+            };
+            println!("{:3} | {} | {}", entry.seq, when, text);
+        }
+
+        Ok(())
+    }
+}
+
+fn summary(seq: u64, command: &str, changes: &[Change]) -> String {
+    let details: Vec<String> = changes.iter().map(Change::describe).collect();
+    format!("#{} {}: {}", seq, command, details.join(", "))
+}
+
+// Refuses to touch a task that no longer matches what the journal expects,
+// e.g. after tasks.json was edited by hand. The store takes all changes at
+// once, so a failure leaves it as the journal says it is.
+fn apply(store: &mut dyn TaskStore, changes: &[Change]) -> Result<()> {
+    for change in changes {
+        let current = store.get(change.id())?;
+        if current != change.before {
+            anyhow::bail!(
+                "Task {} was changed outside the journal; refusing to rewrite it",
+                change.id()
+            );
+        }
+    }
+
+    let edits: Vec<Edit<'_>> = changes
+        .iter()
+        .map(|change| match &change.after {
+            Some(task) => Edit::Put(task),
+            None => Edit::Remove(change.id()),
+        })
+        .collect();
+    store.apply(&edits)
+}
@@ -0,0 +1,1187 @@
+mod formats;
+mod graph;
+mod journal;
+mod model;
+mod ops;
+mod recurrence;
+mod search;
+mod server;
+mod store;
+mod timesheet;
+
+use anyhow::{Context, Result};
+use chrono::{Local, NaiveDate};
+use formats::{Format, ImportedTask};
+use graph::TaskGraph;
+use journal::{Change, Journal};
+use model::{NewTask, Task, TimeEntry};
+use ops::{ListFilter, parse_tag};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+use store::{Edit, JsonStore, SqliteStore, StoreConfig, StoreKind, StoreLock, TaskStore};
+use timesheet::GroupBy;
+
+// -----------------------------
+// Command-line Options
+// -----------------------------
+#[derive(Debug)]
+struct ExportOptions {
+    format: Format,
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum ConflictPolicy {
+    // Give the imported task a fresh id and keep both.
+    #[default]
+    Renumber,
+    Skip,
+    Overwrite,
+}
+
+impl FromStr for ConflictPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "renumber" => Ok(ConflictPolicy::Renumber),
+            "skip" => Ok(ConflictPolicy::Skip),
+            "overwrite" => Ok(ConflictPolicy::Overwrite),
+            other => anyhow::bail!(
+                "Unknown conflict policy '{}' (use renumber, skip or overwrite)",
+                other
+            ),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ImportOptions {
+    input: PathBuf,
+    format: Format,
+    on_conflict: ConflictPolicy,
+    dry_run: bool,
+}
+
+#[derive(Debug)]
+struct SearchOptions {
+    query: String,
+    open_only: bool,
+    limit: usize,
+}
+
+#[derive(Debug)]
+struct ReportOptions {
+    since: Option<NaiveDate>,
+    until: Option<NaiveDate>,
+    group_by: GroupBy,
+    json: bool,
+}
+
+#[derive(Debug)]
+struct MigrateOptions {
+    from: PathBuf,
+    to: PathBuf,
+    force: bool,
+}
+
+fn flag_value<'a>(flag: &str, iter: &mut impl Iterator<Item = &'a String>) -> Result<&'a str> {
+    iter.next()
+        .map(String::as_str)
+        .with_context(|| format!("Missing value for {}", flag))
+}
+
+fn parse_due(value: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .with_context(|| format!("Invalid due date '{}' (expected YYYY-MM-DD)", value))
+}
+
+fn parse_id(value: Option<&String>) -> Result<u32> {
+    value
+        .context("Task ID missing")?
+        .parse()
+        .context("Invalid task ID")
+}
+
+// Storage flags may appear anywhere; everything else is left in order.
+fn parse_store_options(args: Vec<String>) -> Result<(StoreConfig, Vec<String>)> {
+    let mut kind = None;
+    let mut path = None;
+    let mut lock_timeout = None;
+    let mut rest = Vec::new();
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--store" => kind = Some(flag_value(arg, &mut iter)?.parse::<StoreKind>()?),
+            "--file" => path = Some(PathBuf::from(flag_value(arg, &mut iter)?)),
+            "--lock-timeout" => {
+                lock_timeout = Some(store::parse_seconds(flag_value(arg, &mut iter)?)?)
+            }
+            _ => rest.push(arg.clone()),
+        }
+    }
+
+    Ok((StoreConfig::resolve(kind, path, lock_timeout)?, rest))
+}
+
+// Title words are everything that is not a flag or a flag value.
+fn parse_new_task(args: &[String]) -> Result<NewTask> {
+    let mut task = NewTask::default();
+    let mut words = Vec::new();
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--priority" | "-p" => task.priority = Some(flag_value(arg, &mut iter)?.parse()?),
+            "--due" | "-d" => task.due = Some(parse_due(flag_value(arg, &mut iter)?)?),
+            "--recur" | "-r" => task.recur = Some(flag_value(arg, &mut iter)?.parse()?),
+            "--note" => task.notes = Some(flag_value(arg, &mut iter)?.to_string()),
+            "--parent" => task.parent = Some(parse_id(iter.next())?),
+            "--blocked-by" | "-b" => {
+                let id = parse_id(iter.next())?;
+                if !task.blocked_by.contains(&id) {
+                    task.blocked_by.push(id);
+                }
+            }
+            "--tag" | "-t" => {
+                let tag = parse_tag(flag_value(arg, &mut iter)?)?;
+                if !task.tags.contains(&tag) {
+                    task.tags.push(tag);
+                }
+            }
+            _ => words.push(arg.as_str()),
+        }
+    }
+
+    task.title = words.join(" ");
+    Ok(task)
+}
+
+fn parse_list_filter(args: &[String]) -> Result<ListFilter> {
+    let mut filter = ListFilter::default();
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--tag" | "-t" => filter.tag = Some(parse_tag(flag_value(arg, &mut iter)?)?),
+            "--overdue" => filter.overdue = true,
+            "--sort" | "-s" => filter.sort = flag_value(arg, &mut iter)?.parse()?,
+            other => anyhow::bail!("Unknown list option '{}'", other),
+        }
+    }
+
+    Ok(filter)
+}
+
+// Query words are everything that is not a flag or a flag value.
+fn parse_search_options(args: &[String]) -> Result<SearchOptions> {
+    let mut options = SearchOptions {
+        query: String::new(),
+        open_only: false,
+        limit: 20,
+    };
+    let mut words = Vec::new();
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--open" => options.open_only = true,
+            "--limit" | "-l" => {
+                options.limit = flag_value(arg, &mut iter)?
+                    .parse()
+                    .context("Invalid result limit")?
+            }
+            _ => words.push(arg.as_str()),
+        }
+    }
+
+    options.query = words.join(" ");
+    if search::tokenize(&options.query).is_empty() {
+        anyhow::bail!("Search query missing");
+    }
+    Ok(options)
+}
+
+fn parse_report_options(args: &[String]) -> Result<ReportOptions> {
+    let mut options = ReportOptions {
+        since: None,
+        until: None,
+        group_by: GroupBy::default(),
+        json: false,
+    };
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--since" => options.since = Some(parse_due(flag_value(arg, &mut iter)?)?),
+            "--until" => options.until = Some(parse_due(flag_value(arg, &mut iter)?)?),
+            "--group-by" | "-g" => options.group_by = flag_value(arg, &mut iter)?.parse()?,
+            "--json" => options.json = true,
+            other => anyhow::bail!("Unknown report option '{}'", other),
+        }
+    }
+
+    if let (Some(since), Some(until)) = (options.since, options.until)
+        && since > until
+    {
+        anyhow::bail!("--since {} is after --until {}", since, until);
+    }
+    Ok(options)
+}
+
+fn parse_export_options(args: &[String]) -> Result<ExportOptions> {
+    let mut format = None;
+    let mut output: Option<PathBuf> = None;
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--format" | "-f" => format = Some(flag_value(arg, &mut iter)?.parse()?),
+            "--output" | "-o" => output = Some(PathBuf::from(flag_value(arg, &mut iter)?)),
+            other => anyhow::bail!("Unknown export option '{}'", other),
+        }
+    }
+
+    let format = format
+        .or_else(|| output.as_deref().and_then(Format::from_path))
+        .context("Export format missing (use --format csv|md|todotxt)")?;
+
+    Ok(ExportOptions { format, output })
+}
+
+fn parse_import_options(args: &[String]) -> Result<ImportOptions> {
+    let mut input = None;
+    let mut format = None;
+    let mut on_conflict = ConflictPolicy::default();
+    let mut dry_run = false;
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--format" | "-f" => format = Some(flag_value(arg, &mut iter)?.parse()?),
+            "--on-conflict" => on_conflict = flag_value(arg, &mut iter)?.parse()?,
+            "--dry-run" | "-n" => dry_run = true,
+            path if input.is_none() && !path.starts_with('-') => input = Some(PathBuf::from(path)),
+            other => anyhow::bail!("Unknown import option '{}'", other),
+        }
+    }
+
+    let input: PathBuf = input.context("Import file missing")?;
+    let format = match format {
+        Some(format) => format,
+        None => Format::from_path(&input).with_context(|| {
+            format!("Cannot tell the format of {} (use --format)", input.display())
+        })?,
+    };
+
+    Ok(ImportOptions {
+        input,
+        format,
+        on_conflict,
+        dry_run,
+    })
+}
+
+fn parse_migrate_options(args: &[String]) -> Result<MigrateOptions> {
+    let mut options = MigrateOptions {
+        from: PathBuf::from(StoreKind::Json.default_path()),
+        to: PathBuf::from(StoreKind::Sqlite.default_path()),
+        force: false,
+    };
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--from" => options.from = PathBuf::from(flag_value(arg, &mut iter)?),
+            "--to" => options.to = PathBuf::from(flag_value(arg, &mut iter)?),
+            "--force" => options.force = true,
+            other => anyhow::bail!("Unknown migrate option '{}'", other),
+        }
+    }
+
+    Ok(options)
+}
+
+fn parse_serve_addr(args: &[String]) -> Result<String> {
+    let mut addr = server::DEFAULT_ADDR.to_string();
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--addr" => addr = flag_value(arg, &mut iter)?.to_string(),
+            other => anyhow::bail!("Unknown serve option '{}'", other),
+        }
+    }
+
+    Ok(addr)
+}
+
+// -----------------------------
+// Entry Point
+// -----------------------------
+fn main() -> Result<()> {
+    let (config, args) = parse_store_options(env::args().collect())?;
+
+    if args.len() < 2 {
+        print_help();
+        return Ok(());
+    }
+
+    let journal = Journal::for_store(&config.path);
+
+    match args[1].as_str() {
+        "add" => {
+            if args.len() < 3 {
+                anyhow::bail!("Task title missing");
+            }
+            let new_task = parse_new_task(&args[2..])?;
+            ops::validate_title(&new_task.title)?;
+            let _lock = config.lock()?;
+            add_task(config.open()?.as_mut(), &journal, new_task)?;
+        }
+        "remove" => {
+            let id = parse_id(args.get(2))?;
+            let _lock = config.lock()?;
+            remove_task(config.open()?.as_mut(), &journal, id)?;
+        }
+        "block" | "unblock" => {
+            let id = parse_id(args.get(2))?;
+            let blockers = args[3..]
+                .iter()
+                .map(|a| parse_id(Some(a)))
+                .collect::<Result<Vec<_>>>()?;
+            if blockers.is_empty() {
+                anyhow::bail!("Blocking task ID missing");
+            }
+            let _lock = config.lock()?;
+            let block = args[1] == "block";
+            set_blockers(config.open()?.as_mut(), &journal, id, &blockers, block)?;
+        }
+        "parent" => {
+            let id = parse_id(args.get(2))?;
+            let parent = match args.get(3) {
+                Some(arg) if arg == "none" => None,
+                other => Some(parse_id(other).context("Parent ID missing (or 'none')")?),
+            };
+            let _lock = config.lock()?;
+            set_parent(config.open()?.as_mut(), &journal, id, parent)?;
+        }
+        "note" => {
+            let id = parse_id(args.get(2))?;
+            let _lock = config.lock()?;
+            set_notes(config.open()?.as_mut(), &journal, id, &args[3..].join(" "))?;
+        }
+        "start" => {
+            let id = parse_id(args.get(2))?;
+            let _lock = config.lock()?;
+            start_timer(config.open()?.as_mut(), &journal, id)?;
+        }
+        "stop" => {
+            let _lock = config.lock()?;
+            stop_timer(config.open()?.as_mut(), &journal)?;
+        }
+        "report" => time_report(config.open()?.as_ref(), &parse_report_options(&args[2..])?)?,
+        "search" => search_tasks(config.open()?.as_ref(), &parse_search_options(&args[2..])?)?,
+        "tree" => tree(config.open()?.as_ref())?,
+        "next" => {
+            let all = match args.get(2).map(String::as_str) {
+                Some("--all" | "-a") => true,
+                Some(other) => anyhow::bail!("Unknown next option '{}'", other),
+                None => false,
+            };
+            next_tasks(config.open()?.as_ref(), all)?;
+        }
+        "complete" => {
+            let id = parse_id(args.get(2))?;
+            let _lock = config.lock()?;
+            complete_task(config.open()?.as_mut(), &journal, id)?;
+        }
+        "due" => {
+            let within = match args.get(2).map(String::as_str) {
+                Some("--within" | "-w") => flag_value("--within", &mut args[3..].iter())?
+                    .parse()
+                    .context("Invalid number of days")?,
+                Some(other) => anyhow::bail!("Unknown due option '{}'", other),
+                None => 0,
+            };
+            due_tasks(config.open()?.as_ref(), within)?;
+        }
+        "undo" => {
+            let _lock = config.lock()?;
+            match journal.undo(config.open()?.as_mut())? {
+                Some(summary) => println!("Undid {}", summary),
+                None => println!("Nothing to undo."),
+            }
+        }
+        "redo" => {
+            let _lock = config.lock()?;
+            match journal.redo(config.open()?.as_mut())? {
+                Some(summary) => println!("Redid {}", summary),
+                None => println!("Nothing to redo."),
+            }
+        }
+        "history" => {
+            let limit = match args.get(2) {
+                Some(n) => n.parse().context("Invalid history length")?,
+                None => 20,
+            };
+            journal.print_history(limit)?;
+        }
+        // Writers swap in a whole file or commit a transaction, so reads need no lock.
+        "list" => list_tasks(config.open()?.as_ref(), &parse_list_filter(&args[2..])?)?,
+        "export" => export_tasks(config.open()?.as_ref(), &parse_export_options(&args[2..])?)?,
+        "import" => {
+            let options = parse_import_options(&args[2..])?;
+            let _lock = config.lock()?;
+            import_tasks(config.open()?.as_mut(), &journal, &options)?;
+        }
+        "migrate" => migrate(&parse_migrate_options(&args[2..])?, config.lock_timeout)?,
+        "serve" => {
+            let addr = parse_serve_addr(&args[2..])?;
+            tokio::runtime::Runtime::new()?.block_on(server::serve(config, &addr))?;
+        }
+        _ => print_help(),
+    }
+
+    Ok(())
+}
+
+// -----------------------------
+// Commands
+// -----------------------------
+fn add_task(store: &mut dyn TaskStore, journal: &Journal, new_task: NewTask) -> Result<()> {
+    let task = ops::add(store, journal, new_task, Local::now().date_naive())?;
+    println!("Task added: {}", task.title);
+    Ok(())
+}
+
+fn remove_task(store: &mut dyn TaskStore, journal: &Journal, id: u32) -> Result<()> {
+    match ops::remove(store, journal, id)? {
+        Some(_) => println!("Task removed."),
+        None => println!("Task not found."),
+    }
+    Ok(())
+}
+
+// Saves one edited task after making sure the graph stays acyclic.
+fn save_linked(
+    store: &mut dyn TaskStore,
+    journal: &Journal,
+    command: &str,
+    mut tasks: Vec<Task>,
+    edited: Task,
+) -> Result<()> {
+    let Some(slot) = tasks.iter_mut().find(|t| t.id == edited.id) else {
+        anyhow::bail!("Task #{} does not exist", edited.id);
+    };
+    let before = std::mem::replace(slot, edited.clone());
+
+    if before == edited {
+        println!("Nothing changed.");
+        return Ok(());
+    }
+
+    TaskGraph::new(&tasks).check_acyclic()?;
+    store.update(&edited)?;
+    journal.record(command, vec![Change::updated(before, edited)])
+}
+
+fn set_blockers(
+    store: &mut dyn TaskStore,
+    journal: &Journal,
+    id: u32,
+    blockers: &[u32],
+    block: bool,
+) -> Result<()> {
+    let tasks = store.list()?;
+    let mut task = tasks
+        .iter()
+        .find(|t| t.id == id)
+        .cloned()
+        .with_context(|| format!("Task #{} does not exist", id))?;
+
+    for &blocker in blockers {
+        if block {
+            if blocker == id {
+                anyhow::bail!("A task cannot block itself");
+            }
+            if !tasks.iter().any(|t| t.id == blocker) {
+                anyhow::bail!("Task #{} does not exist", blocker);
+            }
+            if !task.blocked_by.contains(&blocker) {
+                task.blocked_by.push(blocker);
+            }
+        } else {
+            task.blocked_by.retain(|&dep| dep != blocker);
+        }
+    }
+
+    let command = if block { "block" } else { "unblock" };
+    save_linked(store, journal, command, tasks, task)?;
+    println!("Task #{} blocked by: {:?}", id, blockers_of(store, id)?);
+    Ok(())
+}
+
+fn blockers_of(store: &dyn TaskStore, id: u32) -> Result<Vec<u32>> {
+    Ok(store.get(id)?.map(|t| t.blocked_by).unwrap_or_default())
+}
+
+fn set_parent(
+    store: &mut dyn TaskStore,
+    journal: &Journal,
+    id: u32,
+    parent: Option<u32>,
+) -> Result<()> {
+    let tasks = store.list()?;
+    let mut task = tasks
+        .iter()
+        .find(|t| t.id == id)
+        .cloned()
+        .with_context(|| format!("Task #{} does not exist", id))?;
+
+    if let Some(parent) = parent {
+        if parent == id {
+            anyhow::bail!("A task cannot be its own parent");
+        }
+        if !tasks.iter().any(|t| t.id == parent) {
+            anyhow::bail!("Task #{} does not exist", parent);
+        }
+    }
+
+    task.parent = parent;
+    save_linked(store, journal, "parent", tasks, task)?;
+    match parent {
+        Some(parent) => println!("Task #{} is now a subtask of #{}", id, parent),
+        None => println!("Task #{} is now a top-level task", id),
+    }
+    Ok(())
+}
+
+fn complete_task(store: &mut dyn TaskStore, journal: &Journal, id: u32) -> Result<()> {
+    match ops::complete(store, journal, id, Local::now().date_naive())? {
+        None => println!("Task not found."),
+        Some(done) if !done.changed => println!("Task is already completed."),
+        Some(done) => {
+            println!("Task marked as completed.");
+            if let Some(next) = done.next {
+                println!(
+                    "Next occurrence #{} due {}",
+                    next.id,
+                    next.due.map(|d| d.to_string()).unwrap_or_default()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn list_tasks(store: &dyn TaskStore, filter: &ListFilter) -> Result<()> {
+    let today = Local::now().date_naive();
+    let tasks = ops::filter_tasks(store.list()?, filter, today);
+
+    if tasks.is_empty() {
+        println!("No tasks found.");
+        return Ok(());
+    }
+
+    println!("ID | Status | Priority | Due         | Title");
+    println!("-------------------------------------------------------");
+
+    for t in tasks {
+        let status = if t.completed { "✔" } else { " " };
+        let priority = t.priority.map(|p| p.to_string()).unwrap_or_default();
+        let due = match t.due {
+            Some(d) if t.is_overdue(today) => format!("{}!", d),
+            Some(d) => d.to_string(),
+            None => String::new(),
+        };
+        let tags: String = t.tags.iter().map(|tag| format!(" #{}", tag)).collect();
+        let recur = t.recur.as_ref().map(|r| format!(" (↻ {})", r)).unwrap_or_default();
+        println!(
+            "{:2} | [{}]    | {:8} | {:11} | {}{}{}",
+            t.id, status, priority, due, t.title, tags, recur
+        );
+    }
+
+    Ok(())
+}
+
+fn set_notes(store: &mut dyn TaskStore, journal: &Journal, id: u32, notes: &str) -> Result<()> {
+    let before = store
+        .get(id)?
+        .with_context(|| format!("Task #{} does not exist", id))?;
+    let mut task = before.clone();
+    task.notes = ops::normalize_notes(notes.to_string());
+
+    if task == before {
+        println!("Notes unchanged.");
+        return Ok(());
+    }
+
+    store.update(&task)?;
+    journal.record("note", vec![Change::updated(before, task.clone())])?;
+    match task.notes {
+        Some(_) => println!("Notes saved for task #{}", id),
+        None => println!("Notes cleared for task #{}", id),
+    }
+    Ok(())
+}
+
+// Only one timer runs at a time; starting another stops the current one.
+fn start_timer(store: &mut dyn TaskStore, journal: &Journal, id: u32) -> Result<()> {
+    let task = store
+        .get(id)?
+        .with_context(|| format!("Task #{} does not exist", id))?;
+    if task.completed {
+        anyhow::bail!("Task #{} is already completed", id);
+    }
+    if task.is_tracking() {
+        println!("Already tracking #{} {}", task.id, task.title);
+        return Ok(());
+    }
+
+    let now = Local::now();
+    let mut changes = stop_running(store, now)?;
+
+    let mut started = task.clone();
+    started.time_log.push(TimeEntry {
+        start: now,
+        end: None,
+    });
+    store.update(&started)?;
+    changes.push(Change::updated(task, started.clone()));
+
+    journal.record("start", changes)?;
+    println!("Started #{} {} at {}", started.id, started.title, now.format("%H:%M"));
+    Ok(())
+}
+
+fn stop_timer(store: &mut dyn TaskStore, journal: &Journal) -> Result<()> {
+    let changes = stop_running(store, Local::now())?;
+    if changes.is_empty() {
+        println!("No timer running.");
+        return Ok(());
+    }
+
+    journal.record("stop", changes)?;
+    Ok(())
+}
+
+fn stop_running(store: &mut dyn TaskStore, now: chrono::DateTime<Local>) -> Result<Vec<Change>> {
+    let mut changes = Vec::new();
+
+    for before in store.list()?.into_iter().filter(Task::is_tracking) {
+        let mut after = before.clone();
+        if let Some(elapsed) = after.stop_timer(now) {
+            println!(
+                "Stopped #{} {} after {}",
+                after.id,
+                after.title,
+                timesheet::format_duration(elapsed)
+            );
+        }
+        store.update(&after)?;
+        changes.push(Change::updated(before, after));
+    }
+
+    Ok(changes)
+}
+
+fn time_report(store: &dyn TaskStore, options: &ReportOptions) -> Result<()> {
+    let tasks = store.list()?;
+    let report = timesheet::report(
+        &tasks,
+        options.since,
+        options.until,
+        options.group_by,
+        Local::now(),
+    );
+
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    if report.rows.is_empty() {
+        println!("No time tracked in this range.");
+        return Ok(());
+    }
+
+    let heading = match options.group_by {
+        GroupBy::Task => "Task",
+        GroupBy::Tag => "Tag",
+        GroupBy::Day => "Day",
+    };
+    let width = report
+        .rows
+        .iter()
+        .map(|row| row.key.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(heading.len());
+
+    println!("{:width$} | {:>9} | {:>7}", heading, "Time", "Hours");
+    println!("{}", "-".repeat(width + 22));
+    for row in &report.rows {
+        println!(
+            "{:width$} | {:>9} | {:>7.2}",
+            row.key,
+            timesheet::format_duration(chrono::Duration::seconds(row.seconds)),
+            row.hours
+        );
+    }
+    println!("{}", "-".repeat(width + 22));
+    println!(
+        "{:width$} | {:>9} | {:>7.2}",
+        "Total",
+        timesheet::format_duration(chrono::Duration::seconds(report.total_seconds)),
+        report.total_hours
+    );
+
+    Ok(())
+}
+
+fn search_tasks(store: &dyn TaskStore, options: &SearchOptions) -> Result<()> {
+    let tasks = store.list()?;
+    let hits: Vec<_> = search::search(&tasks, &options.query)
+        .into_iter()
+        .filter(|hit| !options.open_only || !hit.task.completed)
+        .take(options.limit)
+        .collect();
+
+    if hits.is_empty() {
+        println!("No tasks match '{}'.", options.query);
+        return Ok(());
+    }
+
+    println!("Score | ID | Status | Title");
+    println!("-------------------------------------------------------");
+
+    for hit in hits {
+        let t = hit.task;
+        let status = if t.completed { "✔" } else { " " };
+        let tags: String = t.tags.iter().map(|tag| format!(" #{}", tag)).collect();
+        println!("{:5.2} | {:2} | [{}]    | {}{}", hit.score, t.id, status, t.title, tags);
+
+        // First line of the notes, shortened, for context.
+        if let Some(line) = t.notes.as_deref().and_then(|n| n.lines().next()) {
+            let snippet: String = line.chars().take(60).collect();
+            let more = if snippet.len() < line.len() { "…" } else { "" };
+            println!("      |    |        |   {}{}", snippet, more);
+        }
+    }
+
+    Ok(())
+}
+
+fn tree(store: &dyn TaskStore) -> Result<()> {
+    let tasks = store.list()?;
+
+    if tasks.is_empty() {
+        println!("No tasks found.");
+        return Ok(());
+    }
+
+    for line in TaskGraph::new(&tasks).render_tree() {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+// Ready tasks only, or with `all` the whole remaining plan in dependency order.
+fn next_tasks(store: &dyn TaskStore, all: bool) -> Result<()> {
+    let tasks = store.list()?;
+    let graph = TaskGraph::new(&tasks);
+    let order = graph.topological_order();
+
+    let mut shown = 0;
+    for t in order {
+        let pending = graph.pending(t.id);
+        if !all && !pending.is_empty() {
+            continue;
+        }
+
+        let waits: Vec<String> = pending.iter().map(|id| format!("#{}", id)).collect();
+        let waits = if waits.is_empty() {
+            String::new()
+        } else {
+            format!("  (after {})", waits.join(", "))
+        };
+        let priority = t.priority.map(|p| format!(" [{}]", p)).unwrap_or_default();
+        println!("#{} {}{}{}", t.id, t.title, priority, waits);
+        shown += 1;
+    }
+
+    if shown == 0 {
+        println!("Nothing to do.");
+    }
+    Ok(())
+}
+
+// Reminder view: overdue tasks, then today's, then the next `within` days.
+fn due_tasks(store: &dyn TaskStore, within: u64) -> Result<()> {
+    let today = Local::now().date_naive();
+    let horizon = today
+        .checked_add_days(chrono::Days::new(within))
+        .context("Look-ahead is too far in the future")?;
+
+    let mut tasks: Vec<Task> = store
+        .list()?
+        .into_iter()
+        .filter(|t| !t.completed && t.due.is_some_and(|due| due <= horizon))
+        .collect();
+    tasks.sort_by_key(|t| (t.due, std::cmp::Reverse(t.priority), t.id));
+
+    if tasks.is_empty() {
+        println!("Nothing due.");
+        return Ok(());
+    }
+
+    for t in tasks {
+        let Some(due) = t.due else { continue };
+        let when = match (due - today).num_days() {
+            days if days < 0 => format!("OVERDUE {}d", -days),
+            0 => "today".to_string(),
+            1 => "tomorrow".to_string(),
+            days => format!("in {}d", days),
+        };
+        let recur = t.recur.as_ref().map(|r| format!(" (↻ {})", r)).unwrap_or_default();
+        println!("{:>11} | {} | #{} {}{}", when, due, t.id, t.title, recur);
+    }
+
+    Ok(())
+}
+
+fn export_tasks(store: &dyn TaskStore, options: &ExportOptions) -> Result<()> {
+    let tasks = store.list()?;
+    let content = formats::export(&tasks, options.format)?;
+
+    match &options.output {
+        Some(path) => {
+            fs::write(path, content)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            println!("Exported {} task(s) to {}", tasks.len(), path.display());
+        }
+        None => print!("{}", content),
+    }
+
+    Ok(())
+}
+
+// What importing one record would do to the store.
+#[derive(Debug)]
+enum ImportAction {
+    Add(Box<Task>),
+    Overwrite { before: Box<Task>, after: Box<Task> },
+    Unchanged(u32),
+    Skip(u32),
+}
+
+fn plan_import(
+    existing: &[Task],
+    imported: Vec<ImportedTask>,
+    policy: ConflictPolicy,
+) -> Vec<ImportAction> {
+    let mut taken: HashSet<u32> = existing.iter().map(|t| t.id).collect();
+    let mut next_id = taken.iter().max().copied().unwrap_or(0) + 1;
+    let mut fresh_id = |taken: &mut HashSet<u32>| {
+        while taken.contains(&next_id) {
+            next_id += 1;
+        }
+        taken.insert(next_id);
+        next_id
+    };
+    let mut actions = Vec::new();
+    // Ids from the file that were given a different id here.
+    let mut renamed: HashMap<u32, u32> = HashMap::new();
+
+    for record in imported {
+        let Some(id) = record.id else {
+            let id = fresh_id(&mut taken);
+            actions.push(ImportAction::Add(Box::new(record.into_task(id))));
+            continue;
+        };
+
+        match existing.iter().find(|t| t.id == id) {
+            None if taken.insert(id) => actions.push(ImportAction::Add(Box::new(record.into_task(id)))),
+            // The same id appeared earlier in the file.
+            None => {
+                let new_id = fresh_id(&mut taken);
+                renamed.insert(id, new_id);
+                actions.push(ImportAction::Add(Box::new(record.into_task(new_id))));
+            }
+            Some(current) => {
+                // Exchange formats carry no time log; keep what was tracked.
+                let candidate = Task {
+                    time_log: current.time_log.clone(),
+                    ..record.into_task(id)
+                };
+                if &candidate == current {
+                    actions.push(ImportAction::Unchanged(id));
+                    continue;
+                }
+                match policy {
+                    ConflictPolicy::Skip => actions.push(ImportAction::Skip(id)),
+                    ConflictPolicy::Overwrite => actions.push(ImportAction::Overwrite {
+                        before: Box::new(current.clone()),
+                        after: Box::new(candidate),
+                    }),
+                    ConflictPolicy::Renumber => {
+                        let new_id = fresh_id(&mut taken);
+                        renamed.insert(id, new_id);
+                        actions.push(ImportAction::Add(Box::new(Task {
+                            id: new_id,
+                            time_log: Vec::new(),
+                            ..candidate
+                        })));
+                    }
+                }
+            }
+        }
+    }
+
+    // Keep subtask and blocker links pointing at the renumbered tasks.
+    let remap = |id: &mut u32| *id = renamed.get(id).copied().unwrap_or(*id);
+    for action in &mut actions {
+        let task = match action {
+            ImportAction::Add(task) => task,
+            ImportAction::Overwrite { after, .. } => after,
+            _ => continue,
+        };
+        task.parent.iter_mut().for_each(remap);
+        task.blocked_by.iter_mut().for_each(remap);
+    }
+
+    actions
+}
+
+fn import_tasks(store: &mut dyn TaskStore, journal: &Journal, options: &ImportOptions) -> Result<()> {
+    let content = fs::read_to_string(&options.input)
+        .with_context(|| format!("Failed to read {}", options.input.display()))?;
+    let imported = formats::import(&content, options.format)
+        .with_context(|| format!("Failed to import {}", options.input.display()))?;
+    let actions = plan_import(&store.list()?, imported, options.on_conflict);
+
+    let mut changes = Vec::new();
+    for action in actions {
+        match action {
+            ImportAction::Add(task) => {
+                println!("add       #{} {}", task.id, task.title);
+                changes.push(Change::added(*task));
+            }
+            ImportAction::Overwrite { before, after } => {
+                println!("overwrite #{} {}", after.id, after.title);
+                changes.push(Change::updated(*before, *after));
+            }
+            ImportAction::Unchanged(id) => println!("unchanged #{}", id),
+            ImportAction::Skip(id) => println!("skip      #{} (id already used)", id),
+        }
+    }
+
+    let mut result = store.list()?;
+    for task in changes.iter().filter_map(|c| c.after.as_ref()) {
+        match result.iter_mut().find(|t| t.id == task.id) {
+            Some(slot) => *slot = task.clone(),
+            None => result.push(task.clone()),
+        }
+    }
+    TaskGraph::new(&result)
+        .check_acyclic()
+        .context("Import would create a dependency cycle")?;
+
+    if options.dry_run {
+        println!("Dry run: {} change(s) not applied.", changes.len());
+        return Ok(());
+    }
+
+    // All or nothing, so a failure cannot leave an import without its
+    // journal entry.
+    let edits: Vec<Edit<'_>> = changes
+        .iter()
+        .filter_map(|c| c.after.as_ref())
+        .map(Edit::Put)
+        .collect();
+    store.apply(&edits)?;
+    println!("Imported {} change(s).", changes.len());
+    journal.record("import", changes)
+}
+
+// Copies every task from a JSON file into a SQLite database, keeping ids.
+// The JSON file is left untouched so the move can be checked before deleting it.
+fn migrate(options: &MigrateOptions, lock_timeout: Duration) -> Result<()> {
+    if !options.from.exists() {
+        anyhow::bail!("{} does not exist", options.from.display());
+    }
+
+    let _source_lock = StoreLock::acquire(&options.from, lock_timeout)?;
+    let _target_lock = StoreLock::acquire(&options.to, lock_timeout)?;
+
+    let tasks = JsonStore::new(&options.from).list()?;
+    let mut target = SqliteStore::open(&options.to)?;
+
+    if !options.force && !target.list()?.is_empty() {
+        anyhow::bail!(
+            "{} already contains tasks (use --force to overwrite)",
+            options.to.display()
+        );
+    }
+
+    target.replace_all(&tasks)?;
+    println!(
+        "Migrated {} task(s) from {} to {}",
+        tasks.len(),
+        options.from.display(),
+        options.to.display()
+    );
+    Ok(())
+}
+
+// -----------------------------
+// Help
+// -----------------------------
+fn print_help() {
+    println!(
+        r#"
+Task Manager CLI
+
+Commands:
+  add <title> [options]   Add a task
+      --priority, -p <low|medium|high>
+      --due, -d <YYYY-MM-DD>
+      --tag, -t <tag>       (repeatable)
+      --recur, -r <rule>    daily, weekly, "every 3 days", 2w or a
+                            cron-like "cron <day-of-month> <month> <day-of-week>"
+      --note <text>         Free-form notes (searchable)
+      --parent <id>         Create as a subtask
+      --blocked-by, -b <id> (repeatable)
+  remove <id>             Remove a task
+  complete <id>           Mark task as completed (recurring tasks
+                          get their next instance created)
+  due [--within, -w <n>]  Overdue tasks, tasks due today and in the next n days
+  note <id> [text...]     Replace a task's notes (no text clears them)
+  search <query> [options]
+                          Rank tasks by how well titles, tags and notes
+                          match; words may be prefixes ("rep" finds "report")
+      --open                Only incomplete tasks
+      --limit, -l <n>       Show at most n results (default 20)
+  start <id>              Start tracking time on a task (stops any other timer)
+  stop                    Stop the running timer
+  report [options]        Total tracked time
+      --since <YYYY-MM-DD>  From this day (inclusive)
+      --until <YYYY-MM-DD>  Up to this day (inclusive)
+      --group-by, -g <task|tag|day>  (default task)
+      --json                Print JSON instead of a table
+  block <id> <by>...      Mark a task as blocked by other tasks
+  unblock <id> <by>...    Remove blocked-by links
+  parent <id> <id|none>   Make a task a subtask of another (or top-level)
+  tree                    Show tasks as a hierarchy
+  next [--all, -a]        Unblocked, incomplete tasks in dependency order
+                          (--all also lists blocked ones and what they wait on)
+  list [options]          List tasks
+      --tag, -t <tag>       Only tasks with this tag
+      --overdue             Only incomplete tasks past their due date
+      --sort, -s <id|priority|due>
+  undo                    Revert the last command that changed tasks
+  redo                    Re-apply the last undone command
+  history [n]             Show the last n journal entries (default 20)
+  export [options]        Write all tasks in another format
+      --format, -f <csv|md|todotxt>
+      --output, -o <path>   Write to a file instead of stdout
+  import <path> [options] Read tasks from CSV, Markdown or todo.txt
+      --format, -f <csv|md|todotxt>  (default: from the file extension)
+      --on-conflict <renumber|skip|overwrite>
+                            What to do when an id is taken (default renumber)
+      --dry-run, -n         Show what would change without writing
+  migrate [options]       Copy a JSON task file into SQLite
+      --from <path>         Source JSON file (default tasks.json)
+      --to <path>           Target database (default tasks.db)
+      --force               Overwrite a non-empty database
+  serve [--addr <host:port>]
+                          Serve the tasks as a JSON API (default 127.0.0.1:8080):
+                          GET /tasks[?tag=&overdue=&sort=], POST /tasks,
+                          GET|DELETE /tasks/<id>, POST /tasks/<id>/complete
+
+Storage (any command):
+  --store <json|sqlite>   Backend to use (env: TASKS_STORE, default json)
+  --file <path>           Task file or database (env: TASKS_FILE)
+  --lock-timeout <secs>   Wait this long for another process to finish
+                          (env: TASKS_LOCK_TIMEOUT, default 5, 0 = fail at once)
+
+Examples:
+  cargo run --bin TaskManagers -- add Learn Rust Ownership
+  cargo run --bin TaskManagers -- add Write report -p high --due 2026-11-01 -t work
+  cargo run --bin TaskManagers -- add Pay rent --recur "cron 1 * *"
+  cargo run --bin TaskManagers -- complete 1
+  cargo run --bin TaskManagers -- due --within 3
+  cargo run --bin TaskManagers -- add Write tests --parent 1 --blocked-by 2
+  cargo run --bin TaskManagers -- next
+  cargo run --bin TaskManagers -- start 2
+  cargo run --bin TaskManagers -- report --since 2026-10-01 --group-by tag
+  cargo run --bin TaskManagers -- note 2 Ask Sam about the Q3 numbers
+  cargo run --bin TaskManagers -- search quarterly rep --open
+  cargo run --bin TaskManagers -- list --tag work --overdue --sort priority
+  cargo run --bin TaskManagers -- remove 1
+  cargo run --bin TaskManagers -- undo
+  cargo run --bin TaskManagers -- export --format md -o tasks.md
+  cargo run --bin TaskManagers -- import todo.txt --dry-run
+  cargo run --bin TaskManagers -- migrate
+  cargo run --bin TaskManagers -- serve --addr 0.0.0.0:8080
+  TASKS_STORE=sqlite cargo run --bin TaskManagers -- list
+"#
+    );
+}
+
+// -----------------------------
+// Tests
+// -----------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_tasks() -> Vec<Task> {
+        let mut report = NewTask {
+            title: "Write report".to_string(),
+            notes: Some("Ask Bob for the Q3 numbers".to_string()),
+            tags: vec!["work".to_string()],
+            ..NewTask::default()
+        }
+        .into_task(1);
+        report.time_log.push(TimeEntry {
+            start: Local::now(),
+            end: None,
+        });
+        let review = NewTask {
+            title: "Review".to_string(),
+            parent: Some(1),
+            ..NewTask::default()
+        }
+        .into_task(2);
+        vec![report, review]
+    }
+
+    #[test]
+    fn re_importing_an_export_changes_nothing() {
+        let existing = sample_tasks();
+
+        for format in [Format::Csv, Format::Markdown, Format::TodoTxt] {
+            let content = formats::export(&existing, format).unwrap();
+            let imported = formats::import(&content, format).unwrap();
+
+            for policy in [ConflictPolicy::Renumber, ConflictPolicy::Overwrite] {
+                let actions = plan_import(&existing, imported.clone(), policy);
+                assert!(
+                    actions.iter().all(|a| matches!(a, ImportAction::Unchanged(_))),
+                    "{:?} with {:?}: {:?}",
+                    format,
+                    policy,
+                    actions
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use crate::recurrence::Recurrence;
+use anyhow::Result;
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+// -----------------------------
+// Priority
+// -----------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    Medium,
+    High,
+}
+
+impl FromStr for Priority {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "low" | "l" => Ok(Priority::Low),
+            "medium" | "med" | "m" => Ok(Priority::Medium),
+            "high" | "h" => Ok(Priority::High),
+            other => anyhow::bail!("Unknown priority '{}' (use low, medium or high)", other),
+        }
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Priority::Low => "low",
+            Priority::Medium => "medium",
+            Priority::High => "high",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+// -----------------------------
+// Task
+// -----------------------------
+// New fields are optional so older tasks.json files still load.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Task {
+    pub id: u32,
+    pub title: String,
+    pub completed: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recur: Option<Recurrence>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blocked_by: Vec<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub time_log: Vec<TimeEntry>,
+}
+
+// One tracked interval; `end` stays empty while the timer runs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimeEntry {
+    pub start: DateTime<Local>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<DateTime<Local>>,
+}
+
+impl Task {
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        !self.completed && self.due.is_some_and(|due| due < today)
+    }
+
+    /// The copy to create when a recurring task is completed.
+    pub fn next_occurrence(&self, id: u32, today: NaiveDate) -> Option<Task> {
+        let recur = self.recur.as_ref()?;
+        let due = recur.next_due(self.due.unwrap_or(today), today)?;
+
+        Some(Task {
+            id,
+            completed: false,
+            due: Some(due),
+            time_log: Vec::new(),
+            ..self.clone()
+        })
+    }
+
+    pub fn is_tracking(&self) -> bool {
+        self.time_log.iter().any(|entry| entry.end.is_none())
+    }
+
+    /// Closes the running interval, if any, and returns how long it ran.
+    pub fn stop_timer(&mut self, now: DateTime<Local>) -> Option<chrono::Duration> {
+        let entry = self.time_log.iter_mut().find(|entry| entry.end.is_none())?;
+        entry.end = Some(now);
+        Some(now - entry.start)
+    }
+}
+
+// A task before the store has assigned it an id; also the body of
+// `POST /tasks`, where every field except the title may be left out.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NewTask {
+    pub title: String,
+    pub priority: Option<Priority>,
+    pub due: Option<NaiveDate>,
+    pub tags: Vec<String>,
+    pub recur: Option<Recurrence>,
+    pub parent: Option<u32>,
+    pub blocked_by: Vec<u32>,
+    pub notes: Option<String>,
+}
+
+impl NewTask {
+    pub fn into_task(self, id: u32) -> Task {
+        Task {
+            id,
+            title: self.title,
+            completed: false,
+            priority: self.priority,
+            due: self.due,
+            tags: self.tags,
+            recur: self.recur,
+            parent: self.parent,
+            blocked_by: self.blocked_by,
+            notes: self.notes,
+            time_log: Vec::new(),
+        }
+    }
+}
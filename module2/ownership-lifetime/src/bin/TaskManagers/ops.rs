@@ -0,0 +1,254 @@
+use crate::graph::TaskGraph;
+use crate::journal::{Change, Journal};
+use crate::model::{NewTask, Task};
+use crate::store::{Edit, TaskStore};
+use anyhow::Result;
+use chrono::{Local, NaiveDate};
+use std::str::FromStr;
+use thiserror::Error;
+
+// -----------------------------
+// Shared Task Operations
+// -----------------------------
+// Used by both the CLI and the HTTP server; nothing here prints.
+
+// Errors callers may want to tell apart from I/O or storage failures.
+#[derive(Debug, Error)]
+pub enum TaskError {
+    #[error("Task #{0} does not exist")]
+    NotFound(u32),
+
+    #[error("{0}")]
+    Invalid(String),
+}
+
+fn invalid(message: impl Into<String>) -> anyhow::Error {
+    TaskError::Invalid(message.into()).into()
+}
+
+const RESERVED: [&str; 22] = [
+    "add", "remove", "list", "complete", "migrate", "undo", "redo", "history", "export",
+    "import", "due", "block", "unblock", "parent", "tree", "next", "serve", "search", "note",
+    "start", "stop", "report",
+];
+
+pub fn validate_title(title: &str) -> Result<()> {
+    if title.trim().is_empty() {
+        return Err(invalid("Task title cannot be empty"));
+    }
+
+    if RESERVED.contains(&title.trim()) {
+        return Err(invalid(format!("'{}' is a reserved command", title)));
+    }
+
+    Ok(())
+}
+
+pub fn parse_tag(value: &str) -> Result<String> {
+    let tag = value.trim().to_lowercase();
+    if tag.is_empty() || tag.contains(char::is_whitespace) {
+        return Err(invalid(format!("Invalid tag '{}'", value)));
+    }
+    Ok(tag)
+}
+
+/// Trims notes; blank notes become `None`.
+pub fn normalize_notes(notes: String) -> Option<String> {
+    let notes = notes.trim();
+    (!notes.is_empty()).then(|| notes.to_string())
+}
+
+// -----------------------------
+// Listing
+// -----------------------------
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortKey {
+    #[default]
+    Id,
+    Priority,
+    Due,
+}
+
+impl FromStr for SortKey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "id" => Ok(SortKey::Id),
+            "priority" => Ok(SortKey::Priority),
+            "due" => Ok(SortKey::Due),
+            other => Err(invalid(format!(
+                "Unknown sort key '{}' (use id, priority or due)",
+                other
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ListFilter {
+    pub tag: Option<String>,
+    pub overdue: bool,
+    pub sort: SortKey,
+}
+
+pub fn filter_tasks(tasks: Vec<Task>, filter: &ListFilter, today: NaiveDate) -> Vec<Task> {
+    let mut selected: Vec<Task> = tasks
+        .into_iter()
+        .filter(|t| filter.tag.as_ref().is_none_or(|tag| t.tags.contains(tag)))
+        .filter(|t| !filter.overdue || t.is_overdue(today))
+        .collect();
+
+    // Tasks without a priority or due date sort after those that have one.
+    match filter.sort {
+        SortKey::Id => selected.sort_by_key(|t| t.id),
+        SortKey::Priority => {
+            selected.sort_by_key(|t| (std::cmp::Reverse(t.priority), t.id))
+        }
+        SortKey::Due => selected.sort_by_key(|t| (t.due.is_none(), t.due, t.id)),
+    }
+
+    selected
+}
+
+// -----------------------------
+// Mutations
+// -----------------------------
+// Callers hold the store lock around each of these.
+
+pub fn add(
+    store: &mut dyn TaskStore,
+    journal: &Journal,
+    mut new_task: NewTask,
+    today: NaiveDate,
+) -> Result<Task> {
+    validate_title(&new_task.title)?;
+    new_task.title = new_task.title.trim().to_string();
+    let mut tags = Vec::new();
+    for tag in &new_task.tags {
+        let tag = parse_tag(tag)?;
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    new_task.tags = tags;
+    new_task.notes = new_task.notes.take().and_then(normalize_notes);
+
+    // A recurring task without a due date starts at its first occurrence.
+    if let (Some(recur), None) = (&new_task.recur, new_task.due) {
+        new_task.due = Some(
+            recur
+                .first_on_or_after(today)
+                .ok_or_else(|| invalid("Recurrence never matches a calendar day"))?,
+        );
+    }
+
+    let mut tasks = store.list()?;
+    for id in new_task.parent.iter().chain(&new_task.blocked_by) {
+        if !tasks.iter().any(|t| t.id == *id) {
+            return Err(TaskError::NotFound(*id).into());
+        }
+    }
+    // The new task only gets an id from the store, so check with a placeholder.
+    let placeholder = tasks.iter().map(|t| t.id).max().unwrap_or(0) + 1;
+    tasks.push(Task {
+        id: placeholder,
+        title: new_task.title.clone(),
+        completed: false,
+        priority: None,
+        due: None,
+        tags: Vec::new(),
+        recur: None,
+        parent: new_task.parent,
+        blocked_by: new_task.blocked_by.clone(),
+        notes: None,
+        time_log: Vec::new(),
+    });
+    TaskGraph::new(&tasks)
+        .check_acyclic()
+        .map_err(|e| invalid(e.to_string()))?;
+
+    let task = store.insert(new_task)?;
+    journal.record("add", vec![Change::added(task.clone())])?;
+    Ok(task)
+}
+
+/// Subtasks move up to the removed task's parent and links to it are
+/// dropped, in one store write and one journal entry so `undo` restores
+/// everything.
+pub fn remove(store: &mut dyn TaskStore, journal: &Journal, id: u32) -> Result<Option<Task>> {
+    let tasks = store.list()?;
+    let Some(removed) = tasks.iter().find(|t| t.id == id).cloned() else {
+        return Ok(None);
+    };
+
+    let mut changes = vec![Change::removed(removed.clone())];
+    for before in tasks.into_iter().filter(|t| t.id != id) {
+        let mut after = before.clone();
+        if after.parent == Some(id) {
+            after.parent = removed.parent;
+        }
+        after.blocked_by.retain(|&dep| dep != id);
+
+        if after != before {
+            changes.push(Change::updated(before, after));
+        }
+    }
+
+    let mut edits = vec![Edit::Remove(id)];
+    edits.extend(changes.iter().filter_map(|c| c.after.as_ref()).map(Edit::Put));
+    store.apply(&edits)?;
+
+    journal.record("remove", changes)?;
+    Ok(Some(removed))
+}
+
+#[derive(Debug)]
+pub struct Completed {
+    pub task: Task,
+    /// Instance created because the task recurs.
+    pub next: Option<Task>,
+    /// `false` if the task was already completed.
+    pub changed: bool,
+}
+
+pub fn complete(
+    store: &mut dyn TaskStore,
+    journal: &Journal,
+    id: u32,
+    today: NaiveDate,
+) -> Result<Option<Completed>> {
+    let Some(before) = store.get(id)? else {
+        return Ok(None);
+    };
+
+    if before.completed {
+        return Ok(Some(Completed {
+            task: before,
+            next: None,
+            changed: false,
+        }));
+    }
+
+    let mut task = before.clone();
+    task.completed = true;
+    task.stop_timer(Local::now());
+    store.update(&task)?;
+
+    let mut changes = vec![Change::updated(before, task.clone())];
+
+    // Completing a recurring task schedules the next instance.
+    let next_id = store.list()?.iter().map(|t| t.id).max().unwrap_or(0) + 1;
+    let next = task.next_occurrence(next_id, today);
+    if let Some(next) = &next {
+        store.put(next)?;
+        changes.push(Change::added(next.clone()));
+    }
+
+    journal.record("complete", changes)?;
+    Ok(Some(Completed {
+        task,
+        next,
+        changed: true,
+    }))
+}
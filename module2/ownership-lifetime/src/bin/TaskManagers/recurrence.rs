@@ -0,0 +1,244 @@
+use anyhow::{Context, Result};
+use chrono::{Datelike, Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+// -----------------------------
+// Recurrence Rules
+// -----------------------------
+// Stored as text, e.g. "daily", "every 3 days" or "cron 1,15 * *".
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Recurrence {
+    EveryDays(u32),
+    Cron(DayPattern),
+}
+
+// How far ahead to look before deciding a pattern never matches (e.g. "31 2 *").
+const SEARCH_LIMIT_DAYS: u64 = 366 * 8;
+
+impl Recurrence {
+    /// First occurrence strictly after `after`.
+    pub fn next_after(&self, after: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Recurrence::EveryDays(days) => after.checked_add_days(Days::new(u64::from(*days))),
+            Recurrence::Cron(pattern) => (1..=SEARCH_LIMIT_DAYS)
+                .filter_map(|n| after.checked_add_days(Days::new(n)))
+                .find(|day| pattern.matches(*day)),
+        }
+    }
+
+    /// First occurrence on or after `day`; used when a task is created.
+    pub fn first_on_or_after(&self, day: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Recurrence::EveryDays(_) => Some(day),
+            Recurrence::Cron(pattern) if pattern.matches(day) => Some(day),
+            Recurrence::Cron(_) => self.next_after(day),
+        }
+    }
+
+    /// Due date of the instance that follows one due on `due`. Occurrences
+    /// already in the past are skipped so a late completion does not create
+    /// a backlog of overdue copies.
+    pub fn next_due(&self, due: NaiveDate, today: NaiveDate) -> Option<NaiveDate> {
+        let mut next = self.next_after(due)?;
+        while next < today {
+            next = self.next_after(next)?;
+        }
+        Some(next)
+    }
+}
+
+impl FromStr for Recurrence {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let text = s.trim().to_ascii_lowercase();
+        let words: Vec<&str> = text.split_whitespace().collect();
+
+        let days = match words.as_slice() {
+            ["daily"] => 1,
+            ["weekly"] => 7,
+            ["every", "day"] => 1,
+            ["every", "week"] => 7,
+            ["every", n, unit] => {
+                let n: u32 = n.parse().with_context(|| format!("Invalid interval '{}'", n))?;
+                let per = unit_days(unit).with_context(|| format!("Unknown interval unit '{}'", unit))?;
+                n.checked_mul(per).context("Recurrence interval is too large")?
+            }
+            ["cron", fields @ ..] => return Ok(Recurrence::Cron(DayPattern::parse(fields)?)),
+            [word] if word.len() > 1 => {
+                // Short forms such as "3d" or "2w".
+                let (n, unit) = word.split_at(word.len() - 1);
+                match (n.parse::<u32>(), unit_days(unit)) {
+                    (Ok(n), Some(per)) => n.checked_mul(per).context("Recurrence interval is too large")?,
+                    _ => anyhow::bail!("Unknown recurrence '{}'", s),
+                }
+            }
+            _ => anyhow::bail!(
+                "Unknown recurrence '{}' (try daily, weekly, \"every 3 days\" or \"cron 1 * *\")",
+                s
+            ),
+        };
+
+        if days == 0 {
+            anyhow::bail!("Recurrence interval must be at least one day");
+        }
+
+        Ok(Recurrence::EveryDays(days))
+    }
+}
+
+fn unit_days(unit: &str) -> Option<u32> {
+    match unit {
+        "d" | "day" | "days" => Some(1),
+        "w" | "week" | "weeks" => Some(7),
+        _ => None,
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Recurrence::EveryDays(1) => write!(f, "daily"),
+            Recurrence::EveryDays(7) => write!(f, "weekly"),
+            Recurrence::EveryDays(n) => write!(f, "every {} days", n),
+            Recurrence::Cron(pattern) => write!(f, "cron {}", pattern),
+        }
+    }
+}
+
+impl TryFrom<String> for Recurrence {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        value.parse()
+    }
+}
+
+impl From<Recurrence> for String {
+    fn from(value: Recurrence) -> Self {
+        value.to_string()
+    }
+}
+
+// -----------------------------
+// Cron-like Day Pattern
+// -----------------------------
+// `day-of-month month day-of-week`, each `*`, a value, a list, a range or a
+// step (`*/2`, `1-15/3`). Weekdays accept 0-7 (Sunday is 0 or 7) or names.
+// The five-field form with minute and hour is accepted too, but those two
+// fields are ignored because due dates have no time of day.
+// As in cron, if both day fields are restricted a day matches either one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DayPattern {
+    fields: [String; 3],
+    month_days: Vec<bool>,
+    months: Vec<bool>,
+    weekdays: Vec<bool>,
+}
+
+const WEEKDAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+const MONTH_NAMES: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+impl DayPattern {
+    fn parse(fields: &[&str]) -> Result<Self> {
+        let fields: [&str; 3] = match fields {
+            [dom, month, dow] => [dom, month, dow],
+            [_minute, _hour, dom, month, dow] => [dom, month, dow],
+            _ => anyhow::bail!(
+                "Cron pattern needs 3 fields (day-of-month month day-of-week), got {}",
+                fields.len()
+            ),
+        };
+
+        let month_days = parse_field(fields[0], 1, 31, &[])
+            .with_context(|| format!("Invalid day-of-month field '{}'", fields[0]))?;
+        let months = parse_field(fields[1], 1, 12, &MONTH_NAMES)
+            .with_context(|| format!("Invalid month field '{}'", fields[1]))?;
+        let mut weekdays = parse_field(fields[2], 0, 7, &WEEKDAY_NAMES)
+            .with_context(|| format!("Invalid day-of-week field '{}'", fields[2]))?;
+        // Fold Sunday-as-7 onto 0.
+        if weekdays[7] {
+            weekdays[0] = true;
+        }
+        weekdays.truncate(7);
+
+        Ok(DayPattern {
+            fields: fields.map(str::to_string),
+            month_days,
+            months,
+            weekdays,
+        })
+    }
+
+    pub fn matches(&self, day: NaiveDate) -> bool {
+        if !self.months[day.month() as usize] {
+            return false;
+        }
+
+        let dom = self.month_days[day.day() as usize];
+        let dow = self.weekdays[day.weekday().num_days_from_sunday() as usize];
+
+        match (self.fields[0].starts_with('*'), self.fields[2].starts_with('*')) {
+            (false, false) => dom || dow,
+            _ => dom && dow,
+        }
+    }
+}
+
+impl fmt::Display for DayPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.fields.join(" "))
+    }
+}
+
+// Returns a lookup table indexed by value, sized `max + 1`.
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<Vec<bool>> {
+    let mut allowed = vec![false; max as usize + 1];
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse().with_context(|| format!("Invalid step '{}'", step))?;
+                if step == 0 {
+                    anyhow::bail!("Step must be at least 1");
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (parse_value(start, min, names)?, parse_value(end, min, names)?)
+        } else {
+            let value = parse_value(range, min, names)?;
+            // "5/10" means every 10th value starting at 5.
+            (value, if step > 1 { max } else { value })
+        };
+
+        if start < min || end > max || start > end {
+            anyhow::bail!("'{}' is outside {}-{}", part, min, max);
+        }
+
+        for value in (start..=end).step_by(step as usize) {
+            allowed[value as usize] = true;
+        }
+    }
+
+    Ok(allowed)
+}
+
+fn parse_value(value: &str, offset: u32, names: &[&str]) -> Result<u32> {
+    if let Some(index) = names.iter().position(|name| value.starts_with(name) && value.len() >= 3) {
+        return Ok(index as u32 + offset);
+    }
+    value
+        .parse()
+        .with_context(|| format!("Invalid value '{}'", value))
+}
@@ -0,0 +1,124 @@
+use crate::model::Task;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+// -----------------------------
+// Full-text Search
+// -----------------------------
+// Each task's title, tags and notes are split into case-folded words and
+// counted per field, word-counter style. A task matches when every query
+// word equals, or is a prefix of, one of its words. Scores add up field
+// weight × term frequency × inverse document frequency, so rare words and
+// title hits rank highest; prefix hits count half as much as whole words.
+const TITLE_WEIGHT: f64 = 3.0;
+const TAG_WEIGHT: f64 = 2.0;
+const NOTES_WEIGHT: f64 = 1.0;
+const PREFIX_FACTOR: f64 = 0.5;
+
+#[derive(Debug)]
+pub struct Hit<'a> {
+    pub task: &'a Task,
+    pub score: f64,
+}
+
+/// Lowercase words, split on anything that is not a letter or digit.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.chars().flat_map(char::to_lowercase).collect())
+        .collect()
+}
+
+fn word_counts(text: &str) -> HashMap<String, u32> {
+    let mut counts = HashMap::new();
+    for word in tokenize(text) {
+        *counts.entry(word).or_insert(0) += 1;
+    }
+    counts
+}
+
+struct Document<'a> {
+    task: &'a Task,
+    fields: [(f64, HashMap<String, u32>); 3],
+}
+
+impl<'a> Document<'a> {
+    fn new(task: &'a Task) -> Self {
+        Document {
+            task,
+            fields: [
+                (TITLE_WEIGHT, word_counts(&task.title)),
+                (TAG_WEIGHT, word_counts(&task.tags.join(" "))),
+                (NOTES_WEIGHT, word_counts(task.notes.as_deref().unwrap_or_default())),
+            ],
+        }
+    }
+
+    fn contains(&self, term: &str) -> bool {
+        self.fields
+            .iter()
+            .any(|(_, counts)| counts.keys().any(|word| word.starts_with(term)))
+    }
+
+    // Log-scaled so one word repeated in long notes does not drown out a title hit.
+    fn term_score(&self, term: &str) -> f64 {
+        let mut score = 0.0;
+        for (weight, counts) in &self.fields {
+            for (word, &count) in counts {
+                if !word.starts_with(term) {
+                    continue;
+                }
+                let exact = if word == term { 1.0 } else { PREFIX_FACTOR };
+                score += weight * exact * (1.0 + f64::from(count).ln());
+            }
+        }
+        score
+    }
+}
+
+/// Matching tasks, best first. Ties go to incomplete tasks, then lower ids.
+pub fn search<'a>(tasks: &'a [Task], query: &str) -> Vec<Hit<'a>> {
+    let mut terms = tokenize(query);
+    terms.sort();
+    terms.dedup();
+    if terms.is_empty() {
+        return Vec::new();
+    }
+
+    let docs: Vec<Document> = tasks.iter().map(Document::new).collect();
+    let total = docs.len() as f64;
+    let idf: Vec<f64> = terms
+        .iter()
+        .map(|term| {
+            let matching = docs.iter().filter(|doc| doc.contains(term)).count();
+            (1.0 + total / matching.max(1) as f64).ln()
+        })
+        .collect();
+
+    let mut hits: Vec<Hit> = docs
+        .iter()
+        .filter_map(|doc| {
+            let mut score = 0.0;
+            for (term, idf) in terms.iter().zip(&idf) {
+                let term_score = doc.term_score(term);
+                if term_score == 0.0 {
+                    return None;
+                }
+                score += term_score * idf;
+            }
+            Some(Hit {
+                task: doc.task,
+                score,
+            })
+        })
+        .collect();
+
+    hits.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(Ordering::Equal)
+            .then(a.task.completed.cmp(&b.task.completed))
+            .then(a.task.id.cmp(&b.task.id))
+    });
+    hits
+}
@@ -0,0 +1,497 @@
+use crate::journal::Journal;
+use crate::model::NewTask;
+use crate::ops::{self, ListFilter, TaskError};
+use crate::store::StoreConfig;
+use anyhow::{Context, Result};
+use chrono::Local;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+// -----------------------------
+// HTTP/JSON API
+// -----------------------------
+// GET    /health
+// GET    /tasks?tag=work&overdue=true&sort=priority
+// POST   /tasks                 {"title": "...", "priority": "high", ...}
+// GET    /tasks/{id}
+// POST   /tasks/{id}/complete
+// DELETE /tasks/{id}
+//
+// Every request opens the same store the CLI uses and takes the same lock,
+// so the two can be used side by side.
+pub const DEFAULT_ADDR: &str = "127.0.0.1:8080";
+
+const MAX_HEADER_BYTES: usize = 16 * 1024;
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+pub async fn serve(config: StoreConfig, addr: &str) -> Result<()> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind {}", addr))?;
+    let local = listener.local_addr()?;
+
+    println!("========================================");
+    println!("  Task Manager API READY");
+    println!("  • http://{}/tasks", local);
+    println!("  • store: {}", config.path.display());
+    println!("  Stop with Ctrl+C");
+    println!("========================================");
+
+    run(listener, Arc::new(config)).await
+}
+
+pub async fn run(listener: TcpListener, config: Arc<StoreConfig>) -> Result<()> {
+    loop {
+        let (socket, addr) = listener.accept().await?;
+        let config = Arc::clone(&config);
+
+        tokio::spawn(async move {
+            if let Err(err) = handle_connection(socket, config).await {
+                eprintln!("{} → {:#}", addr, err);
+            }
+        });
+    }
+}
+
+// -----------------------------
+// Request / Response
+// -----------------------------
+#[derive(Debug)]
+struct Request {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn query(&self, key: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+#[derive(Debug)]
+struct Response {
+    status: u16,
+    body: Option<Value>,
+}
+
+impl Response {
+    fn json(status: u16, body: Value) -> Self {
+        Response {
+            status,
+            body: Some(body),
+        }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Response::json(status, json!({ "error": message.into() }))
+    }
+
+    fn to_http(&self) -> String {
+        let reason = match self.status {
+            200 => "OK",
+            201 => "Created",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            413 => "Payload Too Large",
+            _ => "Internal Server Error",
+        };
+        let body = self
+            .body
+            .as_ref()
+            .map(|b| b.to_string())
+            .unwrap_or_default();
+
+        format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status,
+            reason,
+            body.len(),
+            body
+        )
+    }
+}
+
+// Maps the shared operations' errors onto status codes.
+impl From<anyhow::Error> for Response {
+    fn from(err: anyhow::Error) -> Self {
+        match err.downcast_ref::<TaskError>() {
+            Some(TaskError::NotFound(_)) => Response::error(404, err.to_string()),
+            Some(TaskError::Invalid(_)) => Response::error(400, err.to_string()),
+            None => Response::error(500, format!("{:#}", err)),
+        }
+    }
+}
+
+async fn handle_connection(mut socket: TcpStream, config: Arc<StoreConfig>) -> Result<()> {
+    let response = match read_request(&mut socket).await {
+        Ok(Some(request)) => {
+            println!("{} {}", request.method, request.path);
+            tokio::task::spawn_blocking(move || route(&config, &request)).await?
+        }
+        Ok(None) => return Ok(()),
+        Err(err) => err,
+    };
+
+    socket.write_all(response.to_http().as_bytes()).await?;
+    socket.shutdown().await?;
+    Ok(())
+}
+
+async fn read_request(socket: &mut TcpStream) -> Result<Option<Request>, Response> {
+    let io_error = |err: std::io::Error| Response::error(400, err.to_string());
+    let mut buffer = Vec::with_capacity(2048);
+    let mut chunk = [0; 2048];
+
+    let header_end = loop {
+        if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        if buffer.len() > MAX_HEADER_BYTES {
+            return Err(Response::error(413, "Request headers too large"));
+        }
+        let n = socket.read(&mut chunk).await.map_err(io_error)?;
+        if n == 0 {
+            return match buffer.is_empty() {
+                true => Ok(None),
+                false => Err(Response::error(400, "Incomplete request")),
+            };
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..header_end]).into_owned();
+    let mut lines = head.lines();
+    let request_line = lines.next().unwrap_or_default();
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(Response::error(400, "Malformed request line"));
+    };
+
+    let mut content_length = 0;
+    for line in lines {
+        if let Some((name, value)) = line.split_once(':')
+            && name.trim().eq_ignore_ascii_case("content-length")
+        {
+            content_length = value
+                .trim()
+                .parse()
+                .map_err(|_| Response::error(400, "Invalid Content-Length"))?;
+        }
+    }
+    if content_length > MAX_BODY_BYTES {
+        return Err(Response::error(413, "Request body too large"));
+    }
+
+    let mut body = buffer.split_off(header_end);
+    while body.len() < content_length {
+        let n = socket.read(&mut chunk).await.map_err(io_error)?;
+        if n == 0 {
+            return Err(Response::error(400, "Request body shorter than Content-Length"));
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+    body.truncate(content_length);
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(k), percent_decode(v))
+        })
+        .collect();
+
+    Ok(Some(Request {
+        method: method.to_ascii_uppercase(),
+        path: path.to_string(),
+        query,
+        body,
+    }))
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' => match text.get(i + 1..i + 3).map(|hex| u8::from_str_radix(hex, 16)) {
+                Some(Ok(byte)) => {
+                    out.push(byte);
+                    i += 2;
+                }
+                _ => out.push(b'%'),
+            },
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+// -----------------------------
+// Routes
+// -----------------------------
+// Runs on a blocking thread: the store and its lock are synchronous.
+fn route(config: &StoreConfig, request: &Request) -> Response {
+    let segments: Vec<&str> = request
+        .path
+        .trim_matches('/')
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    let result = match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["health"]) => Ok(Response::json(200, json!({ "status": "ok" }))),
+        ("GET", ["tasks"]) => list(config, request),
+        ("POST", ["tasks"]) => create(config, &request.body),
+        ("GET", ["tasks", id]) => get(config, id),
+        ("POST", ["tasks", id, "complete"]) => complete(config, id),
+        ("DELETE", ["tasks", id]) => remove(config, id),
+        (_, ["tasks"] | ["tasks", _] | ["tasks", _, "complete"]) => {
+            Ok(Response::error(405, "Method not allowed"))
+        }
+        _ => Ok(Response::error(404, "No such endpoint")),
+    };
+
+    result.unwrap_or_else(Response::from)
+}
+
+fn parse_id(id: &str) -> Result<u32> {
+    id.parse()
+        .map_err(|_| TaskError::Invalid(format!("Invalid task ID '{}'", id)).into())
+}
+
+fn list(config: &StoreConfig, request: &Request) -> Result<Response> {
+    let filter = ListFilter {
+        tag: request.query("tag").map(ops::parse_tag).transpose()?,
+        overdue: matches!(request.query("overdue"), Some("true" | "1" | "")),
+        sort: request.query("sort").unwrap_or("id").parse()?,
+    };
+
+    let tasks = ops::filter_tasks(config.open()?.list()?, &filter, Local::now().date_naive());
+    Ok(Response::json(200, serde_json::to_value(tasks)?))
+}
+
+fn get(config: &StoreConfig, id: &str) -> Result<Response> {
+    let id = parse_id(id)?;
+    let task = config.open()?.get(id)?.ok_or(TaskError::NotFound(id))?;
+    Ok(Response::json(200, serde_json::to_value(task)?))
+}
+
+fn create(config: &StoreConfig, body: &[u8]) -> Result<Response> {
+    let new_task: NewTask = serde_json::from_slice(body)
+        .map_err(|err| TaskError::Invalid(format!("Invalid task JSON: {}", err)))?;
+
+    let _lock = config.lock()?;
+    let journal = Journal::for_store(&config.path);
+    let task = ops::add(
+        config.open()?.as_mut(),
+        &journal,
+        new_task,
+        Local::now().date_naive(),
+    )?;
+    Ok(Response::json(201, serde_json::to_value(task)?))
+}
+
+fn complete(config: &StoreConfig, id: &str) -> Result<Response> {
+    let id = parse_id(id)?;
+
+    let _lock = config.lock()?;
+    let journal = Journal::for_store(&config.path);
+    let done = ops::complete(
+        config.open()?.as_mut(),
+        &journal,
+        id,
+        Local::now().date_naive(),
+    )?
+    .ok_or(TaskError::NotFound(id))?;
+
+    Ok(Response::json(
+        200,
+        json!({ "task": done.task, "next": done.next, "changed": done.changed }),
+    ))
+}
+
+fn remove(config: &StoreConfig, id: &str) -> Result<Response> {
+    let id = parse_id(id)?;
+
+    let _lock = config.lock()?;
+    let journal = Journal::for_store(&config.path);
+    let removed = ops::remove(config.open()?.as_mut(), &journal, id)?
+        .ok_or(TaskError::NotFound(id))?;
+    Ok(Response::json(200, serde_json::to_value(removed)?))
+}
+
+// -----------------------------
+// Tests
+// -----------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{JsonStore, StoreKind, TaskStore};
+    use std::net::SocketAddr;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    // Each test gets its own directory so they can run in parallel.
+    fn test_config(name: &str, kind: StoreKind) -> StoreConfig {
+        let dir = std::env::temp_dir().join(format!("task-server-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        StoreConfig {
+            kind,
+            path: dir.join(PathBuf::from(kind.default_path())),
+            lock_timeout: Duration::from_secs(5),
+        }
+    }
+
+    async fn start(config: &StoreConfig) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(run(listener, Arc::new(config.clone())));
+        addr
+    }
+
+    async fn request(addr: SocketAddr, method: &str, path: &str, body: &str) -> (u16, Value) {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let request = format!(
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+            method,
+            path,
+            body.len(),
+            body
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+        (status, serde_json::from_str(body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn add_list_complete_remove() {
+        let config = test_config("crud", StoreKind::Json);
+        let addr = start(&config).await;
+
+        let (status, task) = request(
+            addr,
+            "POST",
+            "/tasks",
+            r#"{"title": "Write report", "priority": "high", "tags": ["Work"]}"#,
+        )
+        .await;
+        assert_eq!(status, 201);
+        assert_eq!(task["id"], 1);
+        assert_eq!(task["tags"], json!(["work"]));
+
+        request(addr, "POST", "/tasks", r#"{"title": "Buy milk"}"#).await;
+
+        let (status, tasks) = request(addr, "GET", "/tasks?tag=work", "").await;
+        assert_eq!(status, 200);
+        assert_eq!(tasks.as_array().unwrap().len(), 1);
+
+        let (status, done) = request(addr, "POST", "/tasks/1/complete", "").await;
+        assert_eq!(status, 200);
+        assert_eq!(done["task"]["completed"], true);
+        assert_eq!(done["next"], Value::Null);
+
+        let (status, task) = request(addr, "GET", "/tasks/1", "").await;
+        assert_eq!(status, 200);
+        assert_eq!(task["completed"], true);
+
+        let (status, removed) = request(addr, "DELETE", "/tasks/2", "").await;
+        assert_eq!(status, 200);
+        assert_eq!(removed["title"], "Buy milk");
+
+        let (_, tasks) = request(addr, "GET", "/tasks", "").await;
+        assert_eq!(tasks.as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn errors_map_to_status_codes() {
+        let config = test_config("errors", StoreKind::Json);
+        let addr = start(&config).await;
+
+        let (status, body) = request(addr, "GET", "/tasks/42", "").await;
+        assert_eq!(status, 404);
+        assert_eq!(body["error"], "Task #42 does not exist");
+
+        assert_eq!(request(addr, "DELETE", "/tasks/42", "").await.0, 404);
+        assert_eq!(request(addr, "GET", "/tasks/abc", "").await.0, 400);
+        assert_eq!(request(addr, "POST", "/tasks", "not json").await.0, 400);
+        assert_eq!(request(addr, "POST", "/tasks", r#"{"title": "  "}"#).await.0, 400);
+        assert_eq!(request(addr, "POST", "/tasks", r#"{"title": "x", "parent": 9}"#).await.0, 404);
+        assert_eq!(request(addr, "GET", "/tasks?sort=size", "").await.0, 400);
+        assert_eq!(request(addr, "PUT", "/tasks", "").await.0, 405);
+        assert_eq!(request(addr, "GET", "/nowhere", "").await.0, 404);
+    }
+
+    #[tokio::test]
+    async fn shares_storage_with_the_cli() {
+        let config = test_config("shared", StoreKind::Json);
+        let addr = start(&config).await;
+
+        // Written by the CLI's store, read over HTTP, and back again.
+        JsonStore::new(&config.path)
+            .insert(NewTask {
+                title: "From the CLI".to_string(),
+                ..NewTask::default()
+            })
+            .unwrap();
+
+        let (_, tasks) = request(addr, "GET", "/tasks", "").await;
+        assert_eq!(tasks[0]["title"], "From the CLI");
+
+        request(addr, "POST", "/tasks", r#"{"title": "From HTTP", "blocked_by": [1]}"#).await;
+
+        let stored = JsonStore::new(&config.path).list().unwrap();
+        assert_eq!(stored.len(), 2);
+        assert_eq!(stored[1].blocked_by, vec![1]);
+
+        // Mutations are journaled, so `undo` works on them as well.
+        let journal = Journal::for_store(&config.path);
+        let mut store = config.open().unwrap();
+        assert!(journal.undo(store.as_mut()).unwrap().is_some());
+        assert_eq!(store.list().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn works_with_sqlite() {
+        let config = test_config("sqlite", StoreKind::Sqlite);
+        let addr = start(&config).await;
+
+        let (status, task) = request(
+            addr,
+            "POST",
+            "/tasks",
+            r#"{"title": "Water plants", "recur": "weekly", "due": "2020-01-06"}"#,
+        )
+        .await;
+        assert_eq!(status, 201);
+        assert_eq!(task["recur"], "weekly");
+
+        let (_, done) = request(addr, "POST", "/tasks/1/complete", "").await;
+        assert_eq!(done["next"]["id"], 2);
+        assert_eq!(done["next"]["recur"], "weekly");
+
+        let (_, tasks) = request(addr, "GET", "/tasks?overdue=true", "").await;
+        assert_eq!(tasks.as_array().unwrap().len(), 0);
+    }
+}
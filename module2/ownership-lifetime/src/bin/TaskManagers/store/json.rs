@@ -0,0 +1,123 @@
+use super::{Edit, TaskStore};
+use crate::model::{NewTask, Task};
+use anyhow::{Context, Result};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// -----------------------------
+// JSON File Store
+// -----------------------------
+// Every operation reads and rewrites the whole file.
+pub struct JsonStore {
+    path: PathBuf,
+}
+
+impl JsonStore {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        JsonStore {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    fn load(&self) -> Result<Vec<Task>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read {}", self.path.display()))?;
+
+        if content.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let tasks: Vec<Task> = serde_json::from_str(&content)
+            .with_context(|| format!("{} is corrupted", self.path.display()))?;
+
+        Ok(tasks)
+    }
+
+    // Atomic file write: write to temp + rename
+    fn save(&self, tasks: &[Task]) -> Result<()> {
+        let mut tmp_file = self.path.clone().into_os_string();
+        tmp_file.push(".tmp");
+        let json = serde_json::to_string_pretty(tasks)
+            .context("Failed to serialize tasks")?;
+
+        {
+            let mut file = fs::File::create(&tmp_file)
+                .context("Failed to create temp file for writing")?;
+            file.write_all(json.as_bytes())
+                .context("Failed to write temp file")?;
+            file.sync_all()
+                .context("Failed to sync temp file")?;
+        }
+
+        fs::rename(&tmp_file, &self.path)
+            .with_context(|| format!("Failed to replace {} atomically", self.path.display()))?;
+
+        Ok(())
+    }
+}
+
+impl TaskStore for JsonStore {
+    fn list(&self) -> Result<Vec<Task>> {
+        let mut tasks = self.load()?;
+        tasks.sort_by_key(|t| t.id);
+        Ok(tasks)
+    }
+
+    fn insert(&mut self, task: NewTask) -> Result<Task> {
+        let mut tasks = self.load()?;
+        let next_id = tasks.iter().map(|t| t.id).max().unwrap_or(0) + 1;
+        let task = task.into_task(next_id);
+
+        tasks.push(task.clone());
+        self.save(&tasks)?;
+        Ok(task)
+    }
+
+    fn update(&mut self, task: &Task) -> Result<bool> {
+        let mut tasks = self.load()?;
+
+        match tasks.iter_mut().find(|t| t.id == task.id) {
+            Some(existing) => *existing = task.clone(),
+            None => return Ok(false),
+        }
+
+        self.save(&tasks)?;
+        Ok(true)
+    }
+
+    fn put(&mut self, task: &Task) -> Result<()> {
+        let mut tasks = self.load()?;
+
+        match tasks.iter_mut().find(|t| t.id == task.id) {
+            Some(existing) => *existing = task.clone(),
+            None => tasks.push(task.clone()),
+        }
+
+        self.save(&tasks)
+    }
+
+    fn replace_all(&mut self, tasks: &[Task]) -> Result<()> {
+        self.save(tasks)
+    }
+
+    fn apply(&mut self, edits: &[Edit<'_>]) -> Result<()> {
+        let mut tasks = self.load()?;
+
+        for edit in edits {
+            match *edit {
+                Edit::Put(task) => match tasks.iter_mut().find(|t| t.id == task.id) {
+                    Some(existing) => *existing = task.clone(),
+                    None => tasks.push(task.clone()),
+                },
+                Edit::Remove(id) => tasks.retain(|t| t.id != id),
+            }
+        }
+
+        self.save(&tasks)
+    }
+}
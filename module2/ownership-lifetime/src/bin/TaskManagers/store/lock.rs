@@ -0,0 +1,85 @@
+use anyhow::{Context, Result};
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// -----------------------------
+// Cross-process Lock
+// -----------------------------
+// Advisory lock on `<task file>.lock`, held for a whole load-modify-save
+// cycle. The OS drops it if the process dies, so there is nothing stale
+// to clean up; the file itself is left in place on purpose.
+pub struct StoreLock {
+    file: File,
+}
+
+const RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+pub fn lock_path(data_path: &Path) -> PathBuf {
+    let mut path = data_path.as_os_str().to_owned();
+    path.push(".lock");
+    PathBuf::from(path)
+}
+
+impl StoreLock {
+    /// Retries until `timeout` has passed; a zero timeout fails at once.
+    pub fn acquire(data_path: &Path, timeout: Duration) -> Result<Self> {
+        let path = lock_path(data_path);
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open lock file {}", path.display()))?;
+        let start = Instant::now();
+
+        loop {
+            match file.try_lock() {
+                Ok(()) => break,
+                Err(TryLockError::WouldBlock) => {
+                    let waited = start.elapsed();
+                    if waited >= timeout {
+                        anyhow::bail!(
+                            "{} is locked by {} (waited {:.1}s, see --lock-timeout)",
+                            data_path.display(),
+                            holder(&path),
+                            waited.as_secs_f32()
+                        );
+                    }
+                    thread::sleep(RETRY_INTERVAL.min(timeout - waited));
+                }
+                Err(TryLockError::Error(err)) => {
+                    return Err(err)
+                        .with_context(|| format!("Failed to lock {}", path.display()));
+                }
+            }
+        }
+
+        let mut lock = StoreLock { file };
+        lock.record_owner();
+        Ok(lock)
+    }
+
+    // Best effort: only used to make the "locked" error more helpful.
+    fn record_owner(&mut self) {
+        if self.file.set_len(0).is_ok() {
+            let _ = write!(self.file, "{}", std::process::id());
+        }
+    }
+}
+
+impl Drop for StoreLock {
+    fn drop(&mut self) {
+        let _ = self.file.set_len(0);
+        let _ = self.file.unlock();
+    }
+}
+
+fn holder(path: &Path) -> String {
+    match fs::read_to_string(path) {
+        Ok(pid) if !pid.trim().is_empty() => format!("process {}", pid.trim()),
+        _ => "another process".to_string(),
+    }
+}
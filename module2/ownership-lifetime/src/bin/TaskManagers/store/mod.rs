@@ -0,0 +1,146 @@
+mod json;
+mod lock;
+mod sqlite;
+
+pub use json::JsonStore;
+pub use lock::StoreLock;
+pub use sqlite::SqliteStore;
+
+use crate::model::{NewTask, Task};
+use anyhow::{Context, Result};
+use std::env;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+// -----------------------------
+// Storage Abstraction
+// -----------------------------
+pub trait TaskStore {
+    /// All tasks ordered by id.
+    fn list(&self) -> Result<Vec<Task>>;
+
+    fn get(&self, id: u32) -> Result<Option<Task>> {
+        Ok(self.list()?.into_iter().find(|t| t.id == id))
+    }
+
+    /// Stores a new task under the next free id and returns it.
+    fn insert(&mut self, task: NewTask) -> Result<Task>;
+
+    /// Overwrites an existing task. Returns `false` if the id is unknown.
+    fn update(&mut self, task: &Task) -> Result<bool>;
+
+    /// Inserts or overwrites a task under its own id.
+    fn put(&mut self, task: &Task) -> Result<()>;
+
+    /// Replaces the whole task list, keeping the given ids.
+    fn replace_all(&mut self, tasks: &[Task]) -> Result<()>;
+
+    /// Applies the edits in order, all or none of them.
+    fn apply(&mut self, edits: &[Edit<'_>]) -> Result<()>;
+}
+
+/// One step of `TaskStore::apply`.
+#[derive(Debug, Clone, Copy)]
+pub enum Edit<'a> {
+    /// Inserts or overwrites a task under its own id.
+    Put(&'a Task),
+    Remove(u32),
+}
+
+// -----------------------------
+// Backend Selection
+// -----------------------------
+pub const STORE_ENV: &str = "TASKS_STORE";
+pub const FILE_ENV: &str = "TASKS_FILE";
+pub const LOCK_TIMEOUT_ENV: &str = "TASKS_LOCK_TIMEOUT";
+
+const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StoreKind {
+    #[default]
+    Json,
+    Sqlite,
+}
+
+impl StoreKind {
+    pub fn default_path(self) -> &'static str {
+        match self {
+            StoreKind::Json => "tasks.json",
+            StoreKind::Sqlite => "tasks.db",
+        }
+    }
+}
+
+impl FromStr for StoreKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(StoreKind::Json),
+            "sqlite" | "db" => Ok(StoreKind::Sqlite),
+            other => anyhow::bail!("Unknown store '{}' (use json or sqlite)", other),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StoreConfig {
+    pub kind: StoreKind,
+    pub path: PathBuf,
+    pub lock_timeout: Duration,
+}
+
+pub fn parse_seconds(value: &str) -> Result<Duration> {
+    let secs: f64 = value
+        .trim()
+        .parse()
+        .with_context(|| format!("Invalid number of seconds '{}'", value))?;
+    Duration::try_from_secs_f64(secs).with_context(|| format!("Invalid timeout '{}'", value))
+}
+
+impl StoreConfig {
+    /// Command-line values win over `TASKS_STORE` / `TASKS_FILE` / `TASKS_LOCK_TIMEOUT`.
+    pub fn resolve(
+        kind: Option<StoreKind>,
+        path: Option<PathBuf>,
+        lock_timeout: Option<Duration>,
+    ) -> Result<Self> {
+        let kind = match kind {
+            Some(kind) => kind,
+            None => match env::var(STORE_ENV) {
+                Ok(value) => value.parse()?,
+                Err(_) => StoreKind::default(),
+            },
+        };
+        let path = path
+            .or_else(|| env::var_os(FILE_ENV).map(PathBuf::from))
+            .unwrap_or_else(|| PathBuf::from(kind.default_path()));
+        let lock_timeout = match lock_timeout {
+            Some(timeout) => timeout,
+            None => match env::var(LOCK_TIMEOUT_ENV) {
+                Ok(value) => parse_seconds(&value)?,
+                Err(_) => DEFAULT_LOCK_TIMEOUT,
+            },
+        };
+
+        Ok(StoreConfig {
+            kind,
+            path,
+            lock_timeout,
+        })
+    }
+
+    /// Hold the returned guard across every read-modify-write of the store.
+    pub fn lock(&self) -> Result<StoreLock> {
+        StoreLock::acquire(&self.path, self.lock_timeout)
+    }
+
+    pub fn open(&self) -> Result<Box<dyn TaskStore>> {
+        Ok(match self.kind {
+            StoreKind::Json => Box::new(JsonStore::new(&self.path)),
+            StoreKind::Sqlite => Box::new(SqliteStore::open(&self.path)?),
+        })
+    }
+}
@@ -0,0 +1,218 @@
+use super::{Edit, TaskStore};
+use crate::model::{NewTask, Priority, Task};
+use anyhow::{Context, Result};
+use rusqlite::types::Type;
+use rusqlite::{Connection, OptionalExtension, Row, Transaction, TransactionBehavior, params};
+use std::path::Path;
+use std::time::Duration;
+
+// -----------------------------
+// SQLite Store
+// -----------------------------
+// Embedded database file; each command touches only the rows it needs.
+pub struct SqliteStore {
+    conn: Connection,
+}
+
+// Applied in order; `PRAGMA user_version` records how many have run.
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE tasks (
+        id        INTEGER PRIMARY KEY,
+        title     TEXT NOT NULL,
+        completed INTEGER NOT NULL DEFAULT 0,
+        priority  TEXT,
+        due       TEXT,
+        tags      TEXT NOT NULL DEFAULT '[]'
+    );",
+    "ALTER TABLE tasks ADD COLUMN recur TEXT;",
+    "ALTER TABLE tasks ADD COLUMN parent INTEGER;
+     ALTER TABLE tasks ADD COLUMN blocked_by TEXT NOT NULL DEFAULT '[]';",
+    "ALTER TABLE tasks ADD COLUMN notes TEXT;",
+    "ALTER TABLE tasks ADD COLUMN time_log TEXT NOT NULL DEFAULT '[]';",
+];
+
+const SELECT_TASK: &str =
+    "SELECT id, title, completed, priority, due, tags, recur, parent, blocked_by, notes, \
+     time_log FROM tasks";
+
+impl SqliteStore {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open database {}", path.display()))?;
+
+        // Wait for other writers instead of failing immediately.
+        conn.busy_timeout(Duration::from_secs(5))?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+
+        let mut store = SqliteStore { conn };
+        store.migrate()?;
+        Ok(store)
+    }
+
+    fn migrate(&mut self) -> Result<()> {
+        let version: usize = self
+            .conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))?;
+
+        if version > MIGRATIONS.len() {
+            anyhow::bail!("Database schema version {} is newer than this program", version);
+        }
+
+        let tx = self.write_tx()?;
+        for sql in &MIGRATIONS[version..] {
+            tx.execute_batch(sql).context("Failed to migrate database schema")?;
+        }
+        tx.pragma_update(None, "user_version", MIGRATIONS.len())?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    // Take the write lock up front so read-then-write cycles never race.
+    fn write_tx(&mut self) -> Result<Transaction<'_>> {
+        Ok(self
+            .conn
+            .transaction_with_behavior(TransactionBehavior::Immediate)?)
+    }
+
+    fn task_from_row(row: &Row<'_>) -> rusqlite::Result<Task> {
+        let priority: Option<String> = row.get("priority")?;
+        let tags: String = row.get("tags")?;
+        let recur: Option<String> = row.get("recur")?;
+        let blocked_by: String = row.get("blocked_by")?;
+        let time_log: String = row.get("time_log")?;
+
+        Ok(Task {
+            id: row.get("id")?,
+            title: row.get("title")?,
+            completed: row.get("completed")?,
+            priority: priority
+                .map(|p| p.parse::<Priority>().map_err(|e| Self::corrupt(row, "priority", e)))
+                .transpose()?,
+            due: row.get("due")?,
+            tags: serde_json::from_str(&tags).map_err(|e| Self::corrupt(row, "tags", e))?,
+            recur: recur
+                .map(|r| r.parse().map_err(|e| Self::corrupt(row, "recur", e)))
+                .transpose()?,
+            parent: row.get("parent")?,
+            blocked_by: serde_json::from_str(&blocked_by)
+                .map_err(|e| Self::corrupt(row, "blocked_by", e))?,
+            notes: row.get("notes")?,
+            time_log: serde_json::from_str(&time_log)
+                .map_err(|e| Self::corrupt(row, "time_log", e))?,
+        })
+    }
+
+    // A column that does not decode is an error: reading it as a default
+    // would let the next write store that default for good.
+    fn corrupt(
+        row: &Row<'_>,
+        column: &str,
+        err: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+    ) -> rusqlite::Error {
+        match row.as_ref().column_index(column) {
+            Ok(index) => rusqlite::Error::FromSqlConversionFailure(index, Type::Text, err.into()),
+            Err(err) => err,
+        }
+    }
+
+    fn write_task(conn: &Connection, task: &Task) -> Result<usize> {
+        let changed = conn.execute(
+            "INSERT OR REPLACE INTO tasks
+                 (id, title, completed, priority, due, tags, recur, parent, blocked_by, notes,
+                  time_log)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                task.id,
+                task.title,
+                task.completed,
+                task.priority.map(|p| p.to_string()),
+                task.due,
+                serde_json::to_string(&task.tags)?,
+                task.recur.as_ref().map(|r| r.to_string()),
+                task.parent,
+                serde_json::to_string(&task.blocked_by)?,
+                task.notes,
+                serde_json::to_string(&task.time_log)?,
+            ],
+        )?;
+        Ok(changed)
+    }
+}
+
+impl TaskStore for SqliteStore {
+    fn list(&self) -> Result<Vec<Task>> {
+        let mut stmt = self.conn.prepare(&format!("{} ORDER BY id", SELECT_TASK))?;
+        let tasks = stmt
+            .query_map([], Self::task_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .context("Failed to read tasks")?;
+        Ok(tasks)
+    }
+
+    fn get(&self, id: u32) -> Result<Option<Task>> {
+        let task = self
+            .conn
+            .query_row(&format!("{} WHERE id = ?1", SELECT_TASK), [id], Self::task_from_row)
+            .optional()
+            .context("Failed to read task")?;
+        Ok(task)
+    }
+
+    fn insert(&mut self, task: NewTask) -> Result<Task> {
+        let tx = self.write_tx()?;
+        let next_id: u32 = tx.query_row("SELECT COALESCE(MAX(id), 0) + 1 FROM tasks", [], |row| {
+            row.get(0)
+        })?;
+        let task = task.into_task(next_id);
+
+        Self::write_task(&tx, &task)?;
+        tx.commit()?;
+        Ok(task)
+    }
+
+    fn update(&mut self, task: &Task) -> Result<bool> {
+        let tx = self.write_tx()?;
+        let exists = tx
+            .query_row("SELECT 1 FROM tasks WHERE id = ?1", [task.id], |_| Ok(()))
+            .optional()?
+            .is_some();
+
+        if exists {
+            Self::write_task(&tx, task)?;
+        }
+        tx.commit()?;
+        Ok(exists)
+    }
+
+    fn put(&mut self, task: &Task) -> Result<()> {
+        Self::write_task(&self.conn, task)?;
+        Ok(())
+    }
+
+    fn replace_all(&mut self, tasks: &[Task]) -> Result<()> {
+        let tx = self.write_tx()?;
+        tx.execute("DELETE FROM tasks", [])?;
+        for task in tasks {
+            Self::write_task(&tx, task)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn apply(&mut self, edits: &[Edit<'_>]) -> Result<()> {
+        let tx = self.write_tx()?;
+        for edit in edits {
+            match *edit {
+                Edit::Put(task) => {
+                    Self::write_task(&tx, task)?;
+                }
+                Edit::Remove(id) => {
+                    tx.execute("DELETE FROM tasks WHERE id = ?1", [id])?;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+use crate::model::Task;
+use anyhow::Result;
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+// -----------------------------
+// Time Reports
+// -----------------------------
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    #[default]
+    Task,
+    Tag,
+    Day,
+}
+
+impl FromStr for GroupBy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "task" => Ok(GroupBy::Task),
+            "tag" => Ok(GroupBy::Tag),
+            "day" => Ok(GroupBy::Day),
+            other => anyhow::bail!("Unknown grouping '{}' (use task, tag or day)", other),
+        }
+    }
+}
+
+// Ordered so tasks sort by id, tags by name (untagged last) and days by date.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum GroupKey {
+    Task(u32, String),
+    Tag(String),
+    Untagged,
+    Day(NaiveDate),
+}
+
+impl fmt::Display for GroupKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GroupKey::Task(id, title) => write!(f, "#{} {}", id, title),
+            GroupKey::Tag(tag) => write!(f, "#{}", tag),
+            GroupKey::Untagged => write!(f, "(untagged)"),
+            GroupKey::Day(day) => write!(f, "{}", day),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReportRow {
+    pub key: String,
+    pub seconds: i64,
+    pub hours: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+    pub group_by: GroupBy,
+    pub rows: Vec<ReportRow>,
+    /// Tracked time in the range; with `--group-by tag` a task with several
+    /// tags shows up in several rows, so the rows can add up to more.
+    pub total_seconds: i64,
+    pub total_hours: f64,
+}
+
+/// Totals tracked time between `since` and `until` (both inclusive, local
+/// days). A running timer counts up to `now`.
+pub fn report(
+    tasks: &[Task],
+    since: Option<NaiveDate>,
+    until: Option<NaiveDate>,
+    group_by: GroupBy,
+    now: DateTime<Local>,
+) -> Report {
+    let in_range = |day: NaiveDate| since.is_none_or(|s| day >= s) && until.is_none_or(|u| day <= u);
+    let mut groups: BTreeMap<GroupKey, i64> = BTreeMap::new();
+    let mut total = 0;
+
+    for task in tasks {
+        for entry in &task.time_log {
+            let end = entry.end.unwrap_or(now);
+
+            for (day, length) in split_by_day(entry.start, end) {
+                if !in_range(day) {
+                    continue;
+                }
+                let seconds = length.num_seconds();
+                total += seconds;
+
+                let keys = match group_by {
+                    GroupBy::Task => vec![GroupKey::Task(task.id, task.title.clone())],
+                    GroupBy::Day => vec![GroupKey::Day(day)],
+                    GroupBy::Tag if task.tags.is_empty() => vec![GroupKey::Untagged],
+                    GroupBy::Tag => task.tags.iter().cloned().map(GroupKey::Tag).collect(),
+                };
+                for key in keys {
+                    *groups.entry(key).or_insert(0) += seconds;
+                }
+            }
+        }
+    }
+
+    Report {
+        since,
+        until,
+        group_by,
+        rows: groups
+            .into_iter()
+            .filter(|(_, seconds)| *seconds > 0)
+            .map(|(key, seconds)| ReportRow {
+                key: key.to_string(),
+                seconds,
+                hours: hours(seconds),
+            })
+            .collect(),
+        total_seconds: total,
+        total_hours: hours(total),
+    }
+}
+
+// Rounded to hundredths, which is what invoices use.
+fn hours(seconds: i64) -> f64 {
+    (seconds as f64 / 36.0).round() / 100.0
+}
+
+// Pieces of [start, end) that fall on each local calendar day.
+fn split_by_day(start: DateTime<Local>, end: DateTime<Local>) -> Vec<(NaiveDate, Duration)> {
+    let mut pieces = Vec::new();
+    let mut current = start;
+
+    while current < end {
+        let day = current.date_naive();
+        let next_midnight = day
+            .succ_opt()
+            .and_then(|next| next.and_hms_opt(0, 0, 0))
+            .and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+            .map_or(end, |midnight| midnight.min(end));
+
+        pieces.push((day, next_midnight - current));
+        current = next_midnight;
+    }
+
+    pieces
+}
+
+/// `2h 05m`, or `45s` for very short intervals.
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.num_seconds().max(0);
+    if seconds < 60 {
+        return format!("{}s", seconds);
+    }
+    format!("{}h {:02}m", seconds / 3600, seconds % 3600 / 60)
+}
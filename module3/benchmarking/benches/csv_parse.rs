@@ -0,0 +1,96 @@
+// Owned vs borrowed records from the csv-reader crate (module2).
+//
+// The input is generated into target/ on the first run and reused while it
+// is big enough (100 MB by default, override with CSV_BENCH_MB). It mixes
+// plain, quoted, escaped and multi-line fields.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use csv_reader::Reader;
+use std::fs::{self, File};
+use std::hint::black_box;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::PathBuf;
+
+fn bench_file() -> PathBuf {
+    let megabytes: u64 = std::env::var("CSV_BENCH_MB")
+        .ok()
+        .and_then(|mb| mb.parse().ok())
+        .unwrap_or(100);
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("target")
+        .join(format!("csv_bench_{}mb.csv", megabytes));
+
+    if fs::metadata(&path).is_ok_and(|meta| meta.len() >= megabytes * 1024 * 1024) {
+        return path;
+    }
+
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    let mut out = BufWriter::new(File::create(&path).unwrap());
+    writeln!(out, "id,name,city,amount,comment").unwrap();
+
+    let mut written = 0;
+    let mut id = 0u64;
+    while written < megabytes * 1024 * 1024 {
+        let comment = match id % 4 {
+            0 => String::new(),
+            1 => "plain comment".to_string(),
+            2 => format!("\"said \"\"hello\"\" {} times\"", id % 97),
+            _ => "\"first line\nsecond line, with comma\"".to_string(),
+        };
+        let line = format!(
+            "{},customer {},\"{}, {}\",{}.{:02},{}\n",
+            id,
+            id % 1000,
+            ["Paris", "London", "New York", "São Paulo"][(id % 4) as usize],
+            ["FR", "UK", "US", "BR"][(id % 4) as usize],
+            id % 10_000,
+            id % 100,
+            comment
+        );
+        out.write_all(line.as_bytes()).unwrap();
+        written += line.len() as u64;
+        id += 1;
+    }
+    out.flush().unwrap();
+    path
+}
+
+fn csv_parse_benchmark(c: &mut Criterion) {
+    let path = bench_file();
+    let size = fs::metadata(&path).unwrap().len();
+
+    let mut group = c.benchmark_group("csv parse");
+    group.throughput(Throughput::Bytes(size));
+    group.sample_size(10);
+
+    group.bench_function("owned", |b| {
+        b.iter(|| {
+            let reader = Reader::new(BufReader::new(File::open(&path).unwrap()));
+            let mut bytes = 0;
+            for record in reader {
+                for field in record.unwrap().fields {
+                    bytes += field.len();
+                }
+            }
+            black_box(bytes)
+        })
+    });
+
+    group.bench_function("borrowed", |b| {
+        b.iter(|| {
+            let mut reader = Reader::new(BufReader::new(File::open(&path).unwrap()));
+            let mut bytes = 0;
+            while let Some(record) = reader.next_borrowed() {
+                for field in record.unwrap().iter() {
+                    bytes += field.len();
+                }
+            }
+            black_box(bytes)
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, csv_parse_benchmark);
+criterion_main!(benches);
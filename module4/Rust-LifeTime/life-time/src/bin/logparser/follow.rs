@@ -0,0 +1,446 @@
+// src/bin/logparser/follow.rs
+// Following a growing log file, like `tail -f`
+//
+// `Follower` hands out the complete lines that were appended since the last
+// call as one `Batch`, so records parsed from it borrow from the follower's
+// buffer until the next call. A partial last line is held back until its
+// newline arrives.
+//
+// Rotation: when the path names a different file than the one open (rename
+// and recreate, as logrotate does), the rest of the old file is read first
+// and then the new file from its start. A file that shrinks, or no longer
+// has a newline where the last line handed out ended, was truncated and is
+// read again from its start. Both are checked only once the open file has
+// nothing more to read.
+//
+// A `Checkpoint` records which file and how far; save the one that comes
+// with each batch once the batch is processed and pass it back as
+// `Start::Resume` to carry on after a restart. If the file was rotated or
+// truncated in the meantime it is read from the start.
+
+use std::fs::{self, File, Metadata};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const READ_CHUNK: usize = 64 * 1024;
+
+// How long the waiting calls sleep between checks.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Start {
+    Beginning,
+    /// Only lines written from now on.
+    End,
+    Resume(Checkpoint),
+}
+
+/// Position after the last line handed out, in the file it came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// Device and inode; `None` where the platform has none.
+    pub file_id: Option<(u64, u64)>,
+    pub offset: u64,
+}
+
+impl Checkpoint {
+    pub fn load(path: &Path) -> io::Result<Checkpoint> {
+        let text = fs::read_to_string(path)?;
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: expected `dev inode offset`", path.display()),
+            )
+        };
+        let numbers: Vec<&str> = text.split_whitespace().collect();
+        let number = |i: usize| numbers[i].parse::<u64>().map_err(|_| invalid());
+        match numbers.len() {
+            1 => Ok(Checkpoint {
+                file_id: None,
+                offset: number(0)?,
+            }),
+            3 => Ok(Checkpoint {
+                file_id: Some((number(0)?, number(1)?)),
+                offset: number(2)?,
+            }),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Written to a temporary file and renamed, so a crash never leaves half a checkpoint.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let text = match self.file_id {
+            Some((dev, ino)) => format!("{} {} {}\n", dev, ino, self.offset),
+            None => format!("{}\n", self.offset),
+        };
+        let mut staged = path.as_os_str().to_owned();
+        staged.push(".tmp");
+        fs::write(&staged, text)?;
+        fs::rename(&staged, path)
+    }
+}
+
+pub struct Batch<'a> {
+    /// Complete lines, each ending in a newline; empty if nothing is new.
+    pub text: &'a str,
+    /// Where to resume once these lines are processed.
+    pub checkpoint: Checkpoint,
+}
+
+pub struct Follower {
+    path: PathBuf,
+    file: File,
+    file_id: Option<(u64, u64)>,
+    // Offset in `file` up to which lines have been handed out.
+    offset: u64,
+    // Bytes read past `offset`: complete lines, then maybe a partial one.
+    buffer: Vec<u8>,
+    // Length of the batch handed out last; dropped on the next call.
+    consumed: usize,
+    // Bytes at the start of `buffer` left over from a rotated-away file.
+    carried: usize,
+}
+
+impl Follower {
+    pub fn open(path: impl Into<PathBuf>, start: Start) -> io::Result<Follower> {
+        let path = path.into();
+        let mut file = File::open(&path)?;
+        let meta = file.metadata()?;
+        let file_id = file_id(&meta);
+
+        let offset = match start {
+            Start::Beginning => 0,
+            Start::End => meta.len(),
+            Start::Resume(checkpoint) => {
+                let same_file = checkpoint.file_id.is_none() || checkpoint.file_id == file_id;
+                if same_file && checkpoint.offset <= meta.len() && ends_line(&mut file, checkpoint.offset)? {
+                    checkpoint.offset
+                } else {
+                    0
+                }
+            }
+        };
+        file.seek(SeekFrom::Start(offset))?;
+
+        Ok(Follower {
+            path,
+            file,
+            file_id,
+            offset,
+            buffer: Vec::new(),
+            consumed: 0,
+            carried: 0,
+        })
+    }
+
+    /// Complete lines appended since the last call, possibly none. Never
+    /// waits. Invalid UTF-8 is an error; the bytes stay unconsumed.
+    pub fn read_available(&mut self) -> io::Result<Batch<'_>> {
+        self.drop_consumed();
+        self.refresh()?;
+
+        let complete = self
+            .buffer
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |newline| newline + 1);
+        let text = std::str::from_utf8(&self.buffer[..complete]).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: invalid UTF-8 near byte {}", self.path.display(), self.offset + err.valid_up_to() as u64),
+            )
+        })?;
+        self.consumed = complete;
+        self.offset += (complete - self.carried) as u64;
+        self.carried = 0;
+        Ok(Batch {
+            text,
+            checkpoint: Checkpoint {
+                file_id: self.file_id,
+                offset: self.offset,
+            },
+        })
+    }
+
+    /// Waits until at least one new line is available, or at most for
+    /// `timeout` if one is given; the batch is empty then.
+    pub fn next_batch(&mut self, timeout: Option<Duration>) -> io::Result<Batch<'_>> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        while !self.has_lines()? && deadline.is_none_or(|deadline| Instant::now() < deadline) {
+            thread::sleep(POLL_INTERVAL);
+        }
+        self.read_available()
+    }
+
+    /// `next_batch` for async code: sleeps on the tokio timer rather than
+    /// the thread. The reads themselves are short reads from a regular file
+    /// and are done inline.
+    pub async fn next_batch_async(&mut self, timeout: Option<Duration>) -> io::Result<Batch<'_>> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        while !self.has_lines()? && deadline.is_none_or(|deadline| Instant::now() < deadline) {
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+        self.read_available()
+    }
+
+    // Reads ahead without handing anything out.
+    fn has_lines(&mut self) -> io::Result<bool> {
+        if self.buffer[self.consumed..].contains(&b'\n') {
+            return Ok(true);
+        }
+        self.refresh()?;
+        Ok(self.buffer[self.consumed..].contains(&b'\n'))
+    }
+
+    // Reads what is new, once the file is known not to have been truncated.
+    // Rotation only matters once the old file has nothing left.
+    fn refresh(&mut self) -> io::Result<()> {
+        if self.truncated()? {
+            self.restart()?;
+        }
+        if self.read_to_end()? == 0 && !self.buffer[self.consumed..].contains(&b'\n') {
+            self.check_rotation()?;
+        }
+        Ok(())
+    }
+
+    fn read_to_end(&mut self) -> io::Result<usize> {
+        let mut total = 0;
+        let mut chunk = [0u8; READ_CHUNK];
+        loop {
+            match self.file.read(&mut chunk) {
+                Ok(0) => return Ok(total),
+                Ok(n) => {
+                    self.buffer.extend_from_slice(&chunk[..n]);
+                    total += n;
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    fn truncated(&mut self) -> io::Result<bool> {
+        let read_up_to = self.offset + (self.buffer.len() - self.consumed - self.carried) as u64;
+        if self.file.metadata()?.len() < read_up_to {
+            return Ok(true);
+        }
+        if self.carried > 0 {
+            return Ok(false);
+        }
+        // Truncated and refilled past the old length between two polls: the
+        // handed-out lines ended in a newline, the new content rarely has
+        // one at the same place.
+        let rewritten = !ends_line(&mut self.file, self.offset)?;
+        self.file.seek(SeekFrom::Start(read_up_to))?;
+        Ok(rewritten)
+    }
+
+    // Only called when the open file is fully read and no complete line is
+    // waiting, so at most a partial line is buffered.
+    fn check_rotation(&mut self) -> io::Result<()> {
+        // Between rename and recreate the path may not exist; try again later.
+        let Ok(meta) = fs::metadata(&self.path) else {
+            return Ok(());
+        };
+        let current = file_id(&meta);
+        if current.is_none() || current == self.file_id {
+            return Ok(());
+        }
+        let Ok(file) = File::open(&self.path) else {
+            return Ok(());
+        };
+        self.file = file;
+        self.file_id = current;
+        self.offset = 0;
+        // A partial last line of the old file cannot continue in the new
+        // one, so it ends here and goes out with the next batch.
+        self.drop_consumed();
+        if !self.buffer.is_empty() {
+            self.buffer.push(b'\n');
+        }
+        self.carried = self.buffer.len();
+        Ok(())
+    }
+
+    // Truncated in place; a partial line from before is gone too.
+    fn restart(&mut self) -> io::Result<()> {
+        self.file.seek(SeekFrom::Start(0))?;
+        self.buffer.clear();
+        self.consumed = 0;
+        self.carried = 0;
+        self.offset = 0;
+        Ok(())
+    }
+
+    fn drop_consumed(&mut self) {
+        self.buffer.drain(..self.consumed);
+        self.consumed = 0;
+    }
+}
+
+// Whether `offset` is the start of the file or just after a newline.
+fn ends_line(file: &mut File, offset: u64) -> io::Result<bool> {
+    if offset == 0 {
+        return Ok(true);
+    }
+    let mut last = [0u8; 1];
+    file.seek(SeekFrom::Start(offset - 1))?;
+    Ok(file.read(&mut last)? == 1 && last[0] == b'\n')
+}
+
+#[cfg(unix)]
+fn file_id(meta: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((meta.dev(), meta.ino()))
+}
+
+// Without inodes a rename cannot be told apart from appends; truncation
+// is still noticed.
+#[cfg(not(unix))]
+fn file_id(_meta: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+// -----------------------------
+// Tests
+// -----------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::OpenOptions;
+    use std::io::Write;
+
+    // Each test gets its own directory so they can run in parallel.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("logparser-follow-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn append(path: &Path, text: &str) {
+        let mut file = OpenOptions::new().append(true).create(true).open(path).unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    fn read(follower: &mut Follower) -> String {
+        follower.read_available().unwrap().text.to_string()
+    }
+
+    // Everything available, over as many calls as it takes.
+    fn read_all(follower: &mut Follower) -> String {
+        let mut text = String::new();
+        for _ in 0..4 {
+            text += &read(follower);
+        }
+        text
+    }
+
+    #[test]
+    fn holds_back_a_partial_line() {
+        let log = test_dir("partial").join("app.log");
+        append(&log, "one\ntw");
+        let mut follower = Follower::open(&log, Start::Beginning).unwrap();
+
+        assert_eq!(read(&mut follower), "one\n");
+        assert_eq!(read(&mut follower), "");
+        append(&log, "o\nthree\n");
+        assert_eq!(read(&mut follower), "two\nthree\n");
+        assert_eq!(follower.read_available().unwrap().checkpoint.offset, 14);
+    }
+
+    #[test]
+    fn from_end_skips_existing_lines() {
+        let log = test_dir("end").join("app.log");
+        append(&log, "old\n");
+        let mut follower = Follower::open(&log, Start::End).unwrap();
+
+        append(&log, "new\n");
+        assert_eq!(read(&mut follower), "new\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn follows_rotation_and_ends_the_partial_line() {
+        let dir = test_dir("rotation");
+        let log = dir.join("app.log");
+        append(&log, "one\ntwo");
+        let mut follower = Follower::open(&log, Start::Beginning).unwrap();
+        assert_eq!(read(&mut follower), "one\n");
+
+        fs::rename(&log, dir.join("app.log.1")).unwrap();
+        append(&log, "three\n");
+        assert_eq!(read_all(&mut follower), "two\nthree\n");
+
+        let checkpoint = follower.read_available().unwrap().checkpoint;
+        assert_eq!(checkpoint.file_id, file_id(&fs::metadata(&log).unwrap()));
+        assert_eq!(checkpoint.offset, 6);
+    }
+
+    #[test]
+    fn rereads_a_file_truncated_in_place() {
+        let log = test_dir("truncate").join("app.log");
+        append(&log, "aaa\nbbb\n");
+        let mut follower = Follower::open(&log, Start::Beginning).unwrap();
+        assert_eq!(read(&mut follower), "aaa\nbbb\n");
+
+        File::create(&log).unwrap();
+        append(&log, "c\n");
+        assert_eq!(read(&mut follower), "c\n");
+        assert_eq!(follower.read_available().unwrap().checkpoint.offset, 2);
+    }
+
+    #[test]
+    fn notices_truncate_then_refill_past_the_old_length() {
+        let log = test_dir("refill").join("app.log");
+        append(&log, "aaa\n");
+        let mut follower = Follower::open(&log, Start::Beginning).unwrap();
+        assert_eq!(read(&mut follower), "aaa\n");
+
+        // Same inode, longer than before, no newline where the old one was.
+        fs::write(&log, "xxxxxxxx\nyy\n").unwrap();
+        assert_eq!(read(&mut follower), "xxxxxxxx\nyy\n");
+    }
+
+    #[test]
+    fn checkpoint_round_trips() {
+        let dir = test_dir("checkpoint");
+        let path = dir.join("state");
+        for checkpoint in [
+            Checkpoint { file_id: Some((7, 42)), offset: 1234 },
+            Checkpoint { file_id: None, offset: 5 },
+        ] {
+            checkpoint.save(&path).unwrap();
+            assert_eq!(Checkpoint::load(&path).unwrap(), checkpoint);
+        }
+
+        fs::write(&path, "1 2\n").unwrap();
+        assert_eq!(Checkpoint::load(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        fs::write(&path, "a b c\n").unwrap();
+        assert_eq!(Checkpoint::load(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn resumes_only_where_the_checkpoint_still_fits() {
+        let log = test_dir("resume").join("app.log");
+        append(&log, "one\ntwo\n");
+        let file_id = file_id(&fs::metadata(&log).unwrap());
+        let resume = |file_id, offset| {
+            let start = Start::Resume(Checkpoint { file_id, offset });
+            read(&mut Follower::open(&log, start).unwrap())
+        };
+
+        assert_eq!(resume(file_id, 4), "two\n");
+        // Not at the start of a line: the file was rewritten.
+        assert_eq!(resume(file_id, 2), "one\ntwo\n");
+        // Past the end: the file was truncated.
+        assert_eq!(resume(file_id, 100), "one\ntwo\n");
+        if let Some((dev, ino)) = file_id {
+            // Another file: the log was rotated.
+            assert_eq!(resume(Some((dev, ino + 1)), 4), "one\ntwo\n");
+        }
+    }
+}
@@ -0,0 +1,556 @@
+// src/bin/logparser/format.rs
+// Log line formats
+//
+// Every format borrows from the line it parses: timestamp, level, message
+// and extra fields are slices of the input, never copies. Quoted values
+// (JSON strings, logfmt and access log quotes) are borrowed without their
+// quotes, with escape sequences left as written. Levels are the exception:
+// known spellings ("warning", "err", syslog severities) map to a canonical
+// `&'static str` so ERROR/WARN/INFO mean the same in every format.
+
+use crate::LogParser;
+use chrono::{DateTime, NaiveDateTime};
+
+pub trait LogFormat {
+    /// Name accepted by `--format`.
+    fn name(&self) -> &'static str;
+
+    fn parse<'a>(&self, line: &'a str) -> Result<LogParser<'a>, &'static str>;
+}
+
+/// Every known format, in the order `detect` prefers them on a tie:
+/// stricter formats first, `Simple` last since it accepts the most.
+pub fn all() -> Vec<Box<dyn LogFormat>> {
+    vec![
+        Box::new(Syslog),
+        Box::new(JsonLines),
+        Box::new(AccessLog),
+        Box::new(Logfmt),
+        Box::new(Simple),
+    ]
+}
+
+pub fn by_name(name: &str) -> Option<Box<dyn LogFormat>> {
+    all().into_iter().find(|format| format.name() == name)
+}
+
+// Enough lines to tell formats apart without reading much of a big file.
+const DETECT_LINES: usize = 20;
+
+/// Picks the format that parses the most of the first non-blank lines.
+pub fn detect(sample: &str) -> Option<Box<dyn LogFormat>> {
+    let lines: Vec<&str> = sample
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .take(DETECT_LINES)
+        .collect();
+
+    let mut best: Option<(usize, Box<dyn LogFormat>)> = None;
+    for format in all() {
+        let parsed = lines.iter().filter(|line| format.parse(line).is_ok()).count();
+        if parsed > 0 && best.as_ref().is_none_or(|(most, _)| parsed > *most) {
+            best = Some((parsed, format));
+        }
+    }
+    best.map(|(_, format)| format)
+}
+
+fn record<'a>(
+    log_line: &'a str,
+    timestamp: &'a str,
+    log_level: &'a str,
+    message: &'a str,
+    fields: Vec<(&'a str, &'a str)>,
+) -> LogParser<'a> {
+    LogParser {
+        log_line,
+        timestamp,
+        message,
+        log_level,
+        fields,
+    }
+}
+
+fn normalize_level(level: &str) -> &str {
+    match level.to_ascii_lowercase().as_str() {
+        "emerg" | "emergency" | "panic" => "EMERG",
+        "alert" => "ALERT",
+        "crit" | "critical" | "fatal" => "CRIT",
+        "error" | "err" => "ERROR",
+        "warn" | "warning" => "WARN",
+        "notice" => "NOTICE",
+        "info" | "information" => "INFO",
+        "debug" => "DEBUG",
+        "trace" => "TRACE",
+        _ => level,
+    }
+}
+
+/// Timestamps as the formats write them: `2023-01-01 10:30:00`, RFC 3339,
+/// `01/Jan/2023:10:38:00 +0000` or Unix seconds/milliseconds. Times with
+/// an offset are converted to UTC; times without one are taken as they are.
+pub fn parse_time(timestamp: &str) -> Option<NaiveDateTime> {
+    let timestamp = timestamp.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(timestamp) {
+        return Some(time.naive_utc());
+    }
+    if let Ok(time) = DateTime::parse_from_str(timestamp, "%d/%b/%Y:%H:%M:%S %z") {
+        return Some(time.naive_utc());
+    }
+    for layout in ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M"] {
+        if let Ok(time) = NaiveDateTime::parse_from_str(timestamp, layout) {
+            return Some(time);
+        }
+    }
+    // Epoch numbers: anything past 10^11 is taken as milliseconds.
+    let number: i64 = timestamp.parse().ok()?;
+    let time = if number.abs() >= 100_000_000_000 {
+        DateTime::from_timestamp_millis(number)
+    } else {
+        DateTime::from_timestamp(number, 0)
+    };
+    time.map(|time| time.naive_utc())
+}
+
+// -----------------------------
+// DATE TIME LEVEL: message
+// -----------------------------
+pub struct Simple;
+
+impl LogFormat for Simple {
+    fn name(&self) -> &'static str {
+        "simple"
+    }
+
+    fn parse<'a>(&self, log_line: &'a str) -> Result<LogParser<'a>, &'static str> {
+        // Split into timestamp and rest
+        let (date, rest) = log_line.split_once(' ')
+            .ok_or("Invalid log format: missing date")?;
+
+        // Split again to get time
+        let (time, rest) = rest.split_once(' ')
+            .ok_or("Invalid log format: missing time")?;
+
+        // Reconstruct full timestamp
+        let timestamp = &log_line[..date.len() + 1 + time.len()];
+        if parse_time(timestamp).is_none() {
+            return Err("Invalid log format: bad timestamp");
+        }
+
+        // Split log level and message
+        let (log_level, message) = rest.split_once(": ")
+            .ok_or("Invalid log format: missing ': ' separator")?;
+
+        Ok(record(log_line, timestamp, log_level, message, Vec::new()))
+    }
+}
+
+// -----------------------------
+// RFC 5424 syslog
+// -----------------------------
+// <PRI>VERSION TIMESTAMP HOSTNAME APP-NAME PROCID MSGID STRUCTURED-DATA [MSG]
+pub struct Syslog;
+
+const SEVERITIES: [&str; 8] = ["EMERG", "ALERT", "CRIT", "ERROR", "WARN", "NOTICE", "INFO", "DEBUG"];
+
+const FACILITIES: [&str; 24] = [
+    "kern", "user", "mail", "daemon", "auth", "syslog", "lpr", "news", "uucp", "cron",
+    "authpriv", "ftp", "ntp", "audit", "alert", "clock", "local0", "local1", "local2",
+    "local3", "local4", "local5", "local6", "local7",
+];
+
+impl LogFormat for Syslog {
+    fn name(&self) -> &'static str {
+        "syslog"
+    }
+
+    fn parse<'a>(&self, log_line: &'a str) -> Result<LogParser<'a>, &'static str> {
+        let rest = log_line
+            .strip_prefix('<')
+            .ok_or("Invalid syslog: missing <PRI>")?;
+        let (pri, rest) = rest.split_once('>').ok_or("Invalid syslog: missing <PRI>")?;
+        let pri: usize = match pri.len() {
+            1..=3 if pri.bytes().all(|b| b.is_ascii_digit()) => pri.parse().unwrap_or(usize::MAX),
+            _ => usize::MAX,
+        };
+        if pri > 191 {
+            return Err("Invalid syslog: PRI must be 0 to 191");
+        }
+
+        let mut header = rest.splitn(7, ' ');
+        let mut next = || header.next().filter(|token| !token.is_empty());
+        let version = next().ok_or("Invalid syslog: missing version")?;
+        if !version.bytes().all(|b| b.is_ascii_digit()) {
+            return Err("Invalid syslog: version is not a number");
+        }
+        let timestamp = next().ok_or("Invalid syslog: missing timestamp")?;
+        let host = next().ok_or("Invalid syslog: missing hostname")?;
+        let app = next().ok_or("Invalid syslog: missing app name")?;
+        let procid = next().ok_or("Invalid syslog: missing process id")?;
+        let msgid = next().ok_or("Invalid syslog: missing message id")?;
+        let rest = header.next().ok_or("Invalid syslog: missing structured data")?;
+
+        let (data, message) = structured_data(rest)?;
+        let message = message.strip_prefix('\u{feff}').unwrap_or(message);
+
+        // "-" is syslog for "no value".
+        let fields = [
+            ("facility", FACILITIES[pri / 8]),
+            ("host", host),
+            ("app", app),
+            ("procid", procid),
+            ("msgid", msgid),
+            ("data", data),
+        ]
+        .into_iter()
+        .filter(|(_, value)| *value != "-")
+        .collect();
+        let timestamp = if timestamp == "-" { "" } else { timestamp };
+
+        Ok(record(log_line, timestamp, SEVERITIES[pri % 8], message, fields))
+    }
+}
+
+// Splits `-` or `[id k="v"][...]` off the front; the message follows a space.
+fn structured_data(rest: &str) -> Result<(&str, &str), &'static str> {
+    let end = if rest.starts_with('-') {
+        1
+    } else {
+        let bytes = rest.as_bytes();
+        let mut i = 0;
+        while bytes.get(i) == Some(&b'[') {
+            let mut quoted = false;
+            loop {
+                i += 1;
+                match bytes.get(i) {
+                    None => return Err("Invalid syslog: unterminated structured data"),
+                    Some(b'\\') if quoted => i += 1,
+                    Some(b'"') => quoted = !quoted,
+                    Some(b']') if !quoted => break,
+                    Some(_) => {}
+                }
+            }
+            i += 1;
+        }
+        if i == 0 {
+            return Err("Invalid syslog: missing structured data");
+        }
+        i
+    };
+
+    match rest[end..].strip_prefix(' ') {
+        Some(message) => Ok((&rest[..end], message)),
+        None if end == rest.len() => Ok((&rest[..end], "")),
+        None => Err("Invalid syslog: expected a space after structured data"),
+    }
+}
+
+// -----------------------------
+// JSON lines
+// -----------------------------
+// One object per line. Top-level values are borrowed as written; nested
+// objects and arrays are kept as their raw JSON text.
+pub struct JsonLines;
+
+const TIME_KEYS: [&str; 4] = ["timestamp", "time", "ts", "@timestamp"];
+const LEVEL_KEYS: [&str; 4] = ["level", "lvl", "severity", "log.level"];
+const MESSAGE_KEYS: [&str; 3] = ["message", "msg", "@message"];
+
+impl LogFormat for JsonLines {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn parse<'a>(&self, log_line: &'a str) -> Result<LogParser<'a>, &'static str> {
+        let pairs = JsonCursor::new(log_line)
+            .object()
+            .ok_or("Invalid JSON line: expected one JSON object")?;
+        Ok(from_pairs(log_line, pairs))
+    }
+}
+
+// Shared by JSON and logfmt: well-known keys become the main parts.
+fn from_pairs<'a>(log_line: &'a str, pairs: Vec<(&'a str, &'a str)>) -> LogParser<'a> {
+    let find = |keys: &[&str]| {
+        pairs
+            .iter()
+            .find(|(key, _)| keys.contains(key))
+            .map(|(_, value)| *value)
+    };
+    let timestamp = find(&TIME_KEYS).unwrap_or("");
+    let level = find(&LEVEL_KEYS).map_or("", normalize_level);
+    let message = find(&MESSAGE_KEYS).unwrap_or("");
+
+    let known = |key: &&str| {
+        TIME_KEYS.contains(key) || LEVEL_KEYS.contains(key) || MESSAGE_KEYS.contains(key)
+    };
+    let fields = pairs.iter().filter(|(key, _)| !known(key)).copied().collect();
+    record(log_line, timestamp, level, message, fields)
+}
+
+struct JsonCursor<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> JsonCursor<'a> {
+    fn new(text: &'a str) -> Self {
+        JsonCursor { text, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\r' | b'\n')) {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, byte: u8) -> Option<()> {
+        self.skip_whitespace();
+        (self.peek() == Some(byte)).then(|| self.pos += 1)
+    }
+
+    // The whole text must be one object.
+    fn object(mut self) -> Option<Vec<(&'a str, &'a str)>> {
+        self.eat(b'{')?;
+        let mut pairs = Vec::new();
+        if self.eat(b'}').is_none() {
+            loop {
+                self.skip_whitespace();
+                let key = self.string()?;
+                self.eat(b':')?;
+                let value = self.value()?;
+                pairs.push((key, value));
+                if self.eat(b',').is_none() {
+                    self.eat(b'}')?;
+                    break;
+                }
+            }
+        }
+        self.skip_whitespace();
+        (self.pos == self.text.len()).then_some(pairs)
+    }
+
+    // Contents between the quotes.
+    fn string(&mut self) -> Option<&'a str> {
+        if self.peek() != Some(b'"') {
+            return None;
+        }
+        let start = self.pos + 1;
+        self.pos = start;
+        loop {
+            match self.peek()? {
+                b'\\' => self.pos += 2,
+                b'"' => break,
+                _ => self.pos += 1,
+            }
+        }
+        self.pos += 1;
+        Some(&self.text[start..self.pos - 1])
+    }
+
+    fn value(&mut self) -> Option<&'a str> {
+        self.skip_whitespace();
+        match self.peek()? {
+            b'"' => self.string(),
+            b'{' | b'[' => {
+                let start = self.pos;
+                let mut depth = 0usize;
+                loop {
+                    match self.peek()? {
+                        b'"' => {
+                            self.string()?;
+                            continue;
+                        }
+                        b'{' | b'[' => depth += 1,
+                        b'}' | b']' => depth -= 1,
+                        _ => {}
+                    }
+                    self.pos += 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                Some(&self.text[start..self.pos])
+            }
+            _ => {
+                let start = self.pos;
+                while !matches!(self.peek(), None | Some(b',' | b'}' | b']' | b' ' | b'\t')) {
+                    self.pos += 1;
+                }
+                (self.pos > start).then(|| &self.text[start..self.pos])
+            }
+        }
+    }
+}
+
+// -----------------------------
+// Apache/nginx access logs
+// -----------------------------
+// Common and combined log format:
+// host ident user [time] "request" status bytes ["referer" "agent"]
+pub struct AccessLog;
+
+impl LogFormat for AccessLog {
+    fn name(&self) -> &'static str {
+        "access"
+    }
+
+    fn parse<'a>(&self, log_line: &'a str) -> Result<LogParser<'a>, &'static str> {
+        let mut tokens = log_line.splitn(4, ' ');
+        let mut next = || tokens.next().filter(|token| !token.is_empty());
+        let remote = next().ok_or("Invalid access log: missing client address")?;
+        let ident = next().ok_or("Invalid access log: missing ident")?;
+        let user = next().ok_or("Invalid access log: missing user")?;
+        let rest = tokens.next().ok_or("Invalid access log: missing time")?;
+
+        let (timestamp, rest) = rest
+            .strip_prefix('[')
+            .and_then(|rest| rest.split_once("] "))
+            .ok_or("Invalid access log: missing [time]")?;
+        let (request, rest) = quoted(rest).ok_or("Invalid access log: missing \"request\"")?;
+
+        let mut tokens = rest.trim_start().splitn(3, ' ');
+        let status = tokens
+            .next()
+            .filter(|s| s.len() == 3 && s.bytes().all(|b| b.is_ascii_digit()))
+            .ok_or("Invalid access log: missing status code")?;
+        let bytes = tokens
+            .next()
+            .filter(|s| *s == "-" || (!s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())))
+            .ok_or("Invalid access log: missing response size")?;
+
+        let mut fields = vec![("remote", remote), ("status", status), ("bytes", bytes)];
+        if ident != "-" {
+            fields.push(("ident", ident));
+        }
+        if user != "-" {
+            fields.push(("user", user));
+        }
+        let mut parts = request.splitn(3, ' ');
+        if let (Some(method), Some(path)) = (parts.next(), parts.next()) {
+            fields.push(("method", method));
+            fields.push(("path", path));
+        }
+        // Combined format; anything nginx appends after the agent is ignored.
+        if let Some(rest) = tokens.next() {
+            let (referer, rest) = quoted(rest).ok_or("Invalid access log: bad \"referer\"")?;
+            let (agent, _) = quoted(rest.trim_start()).ok_or("Invalid access log: bad \"agent\"")?;
+            fields.push(("referer", referer));
+            fields.push(("agent", agent));
+        }
+
+        let level = match status.as_bytes()[0] {
+            b'5' => "ERROR",
+            b'4' => "WARN",
+            _ => "INFO",
+        };
+        Ok(record(log_line, timestamp, level, request, fields))
+    }
+}
+
+// `"..."` at the front, with backslash escapes: (contents, rest).
+fn quoted(text: &str) -> Option<(&str, &str)> {
+    let bytes = text.as_bytes();
+    if bytes.first() != Some(&b'"') {
+        return None;
+    }
+    let mut i = 1;
+    loop {
+        match bytes.get(i)? {
+            b'\\' => i += 2,
+            b'"' => return Some((&text[1..i], &text[i + 1..])),
+            _ => i += 1,
+        }
+    }
+}
+
+// -----------------------------
+// logfmt
+// -----------------------------
+// key=value pairs separated by spaces; values with spaces are quoted.
+pub struct Logfmt;
+
+impl LogFormat for Logfmt {
+    fn name(&self) -> &'static str {
+        "logfmt"
+    }
+
+    fn parse<'a>(&self, log_line: &'a str) -> Result<LogParser<'a>, &'static str> {
+        let mut pairs = Vec::new();
+        let mut rest = log_line.trim();
+        while !rest.is_empty() {
+            let (key, after) = rest
+                .split_once('=')
+                .ok_or("Invalid logfmt: expected key=value")?;
+            if key.is_empty() || key.contains([' ', '"']) {
+                return Err("Invalid logfmt: expected key=value");
+            }
+            let (value, after) = if after.starts_with('"') {
+                quoted(after).ok_or("Invalid logfmt: unterminated quote")?
+            } else {
+                after.split_at(after.find(' ').unwrap_or(after.len()))
+            };
+            if !(after.is_empty() || after.starts_with(' ')) {
+                return Err("Invalid logfmt: expected a space between pairs");
+            }
+            pairs.push((key, value));
+            rest = after.trim_start();
+        }
+        if pairs.is_empty() {
+            return Err("Invalid logfmt: empty line");
+        }
+        Ok(from_pairs(log_line, pairs))
+    }
+}
+
+// -----------------------------
+// Tests
+// -----------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detected(sample: &str) -> &'static str {
+        detect(sample).map_or("none", |format| format.name())
+    }
+
+    #[test]
+    fn detects_simple() {
+        assert_eq!(detected("2023-01-01 10:30:00 INFO: Server started"), "simple");
+    }
+
+    #[test]
+    fn detects_syslog() {
+        let line = "<165>1 2023-01-01T10:36:00.003Z web01 checkout 4321 ID47 - Payment: declined";
+        assert_eq!(detected(line), "syslog");
+    }
+
+    #[test]
+    fn detects_json_with_colon_space() {
+        let line = r#"{"ts": "2023-01-01T10:37:00Z", "level": "warn", "msg": "Slow query"}"#;
+        assert_eq!(detected(line), "json");
+        let log = JsonLines.parse(line).unwrap();
+        assert_eq!(log.get_log_level(), "WARN");
+    }
+
+    #[test]
+    fn detects_access_log() {
+        let line = r#"127.0.0.1 - frank [01/Jan/2023:10:38:00 +0000] "GET /api: HTTP/1.1" 503 512 "-" "curl/8.0""#;
+        assert_eq!(detected(line), "access");
+    }
+
+    #[test]
+    fn detects_logfmt_with_colon_space() {
+        let line = r#"time=2023-01-01T10:39:00Z level=error msg="cache miss: storm""#;
+        assert_eq!(detected(line), "logfmt");
+    }
+
+    #[test]
+    fn simple_rejects_bad_timestamp() {
+        assert!(Simple.parse("not a: timestamp").is_err());
+    }
+}
@@ -0,0 +1,406 @@
+// src/bin/logparser/index.rs
+// Sparse time index for large log files
+//
+// To answer "what happened between 14:02 and 14:05" without reading a 20 GB
+// file, the index keeps one entry about every `stride` bytes: the offset of
+// a line with a timestamp there. It is stored beside the log as `<log>.idx`
+// and brought up to date when the log has grown; a log that was replaced or
+// truncated gets a new index.
+//
+// Each entry also records the latest time before its offset and the
+// earliest time from its offset on. A lookup can then skip everything
+// before the last entry that is entirely too early and everything after the
+// first entry that is entirely too late, and stays exact when timestamps
+// are out of order: the region to read just grows, up to the whole file
+// when there is no order at all.
+
+use crate::format::{LogFormat, parse_time};
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_STRIDE: u64 = 1 << 20;
+
+// Fingerprinted to notice a log that was replaced rather than appended to.
+const HEAD_BYTES: usize = 4096;
+
+const MAGIC: &str = "logparser-index 1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Entry {
+    offset: u64,
+    /// Latest time before `offset`, `i64::MIN` if none.
+    max_before: i64,
+    /// Earliest time from `offset` on, `i64::MAX` if none.
+    min_after: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freshness {
+    Loaded,
+    Extended,
+    Built,
+}
+
+#[derive(Debug)]
+pub struct TimeIndex {
+    format: String,
+    stride: u64,
+    /// Bytes of the log covered, always up to a line break.
+    length: u64,
+    head: u64,
+    max_time: i64,
+    entries: Vec<Entry>,
+}
+
+pub fn index_path(log: &Path) -> PathBuf {
+    let mut path = log.as_os_str().to_owned();
+    path.push(".idx");
+    PathBuf::from(path)
+}
+
+/// Milliseconds since the epoch, for comparing and storing.
+pub fn millis(timestamp: &str) -> Option<i64> {
+    parse_time(timestamp).map(|time| time.and_utc().timestamp_millis())
+}
+
+impl TimeIndex {
+    /// The stored index if it still fits `data`, updated for appended
+    /// lines; otherwise a new one. Saved again whenever it changed; a
+    /// failed save only costs the next run a rebuild.
+    pub fn open(
+        log: &Path,
+        data: &[u8],
+        format: &dyn LogFormat,
+        stride: u64,
+    ) -> io::Result<(TimeIndex, Freshness)> {
+        let path = index_path(log);
+        let stored = match fs::read_to_string(&path) {
+            Ok(text) => TimeIndex::parse(&text),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => return Err(err),
+        };
+
+        let (mut index, mut freshness) = match stored {
+            Some(index) if index.fits(data, format, stride) => (index, Freshness::Loaded),
+            _ => (TimeIndex::empty(format, stride), Freshness::Built),
+        };
+        if index.scan(data, format) && freshness == Freshness::Loaded {
+            freshness = Freshness::Extended;
+        }
+
+        if freshness != Freshness::Loaded
+            && let Err(err) = fs::write(&path, index.to_text())
+        {
+            eprintln!("Cannot save {}: {}", path.display(), err);
+        }
+        Ok((index, freshness))
+    }
+
+    fn empty(format: &dyn LogFormat, stride: u64) -> TimeIndex {
+        TimeIndex {
+            format: format.name().to_string(),
+            stride,
+            length: 0,
+            head: fingerprint(&[]),
+            max_time: i64::MIN,
+            entries: Vec::new(),
+        }
+    }
+
+    fn fits(&self, data: &[u8], format: &dyn LogFormat, stride: u64) -> bool {
+        let head = &data[..data.len().min(self.length as usize).min(HEAD_BYTES)];
+        self.format == format.name()
+            && self.stride == stride
+            && self.length <= data.len() as u64
+            && self.head == fingerprint(head)
+    }
+
+    pub fn entries(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Byte range of `data` holding every line timed within `from..to`,
+    /// plus lines around them; the caller filters by time.
+    pub fn region(&self, from: i64, to: i64, data: &[u8], format: &dyn LogFormat) -> (usize, usize) {
+        // Everything before an entry is too early if its latest time is.
+        let first = self.entries.partition_point(|entry| entry.max_before < from);
+        let start = first.checked_sub(1).map_or(0, |i| self.entries[i].offset as usize);
+
+        // Everything from an entry on is too late if its earliest time is,
+        // counting a last line still missing its line break.
+        let tail_min = data[self.length as usize..]
+            .split(|&b| b == b'\n')
+            .filter_map(|line| line_time(line, format))
+            .min()
+            .unwrap_or(i64::MAX);
+        let last = self.entries.partition_point(|entry| entry.min_after.min(tail_min) < to);
+        let end = self.entries.get(last).map_or(data.len(), |entry| entry.offset as usize);
+        (start, end.max(start))
+    }
+
+    // Adds the complete lines past `length`; true if there were any.
+    fn scan(&mut self, data: &[u8], format: &dyn LogFormat) -> bool {
+        let start = self.length as usize;
+        let end = data.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+        if end <= start {
+            return false;
+        }
+
+        let mut next_entry = self.entries.last().map_or(0, |entry| entry.offset + self.stride);
+        // Earliest time per stretch: before the first new entry, then from
+        // each new entry to the next.
+        let mut before_new = i64::MAX;
+        let mut new_entries: Vec<Entry> = Vec::new();
+        let mut stretch_min: Vec<i64> = Vec::new();
+
+        let mut offset = start as u64;
+        for line in data[start..end].split_inclusive(|&b| b == b'\n') {
+            let line_start = offset;
+            offset += line.len() as u64;
+            let Some(time) = line_time(line, format) else {
+                continue;
+            };
+            if line_start >= next_entry {
+                new_entries.push(Entry {
+                    offset: line_start,
+                    max_before: self.max_time,
+                    min_after: i64::MAX,
+                });
+                stretch_min.push(i64::MAX);
+                next_entry = line_start + self.stride;
+            }
+            match stretch_min.last_mut() {
+                Some(min) => *min = (*min).min(time),
+                None => before_new = before_new.min(time),
+            }
+            self.max_time = self.max_time.max(time);
+        }
+
+        let mut running = i64::MAX;
+        for (entry, min) in new_entries.iter_mut().zip(&stretch_min).rev() {
+            running = running.min(*min);
+            entry.min_after = running;
+        }
+        // Older entries see the new lines too.
+        let added_min = running.min(before_new);
+        for entry in &mut self.entries {
+            entry.min_after = entry.min_after.min(added_min);
+        }
+        self.entries.extend(new_entries);
+
+        self.length = end as u64;
+        self.head = fingerprint(&data[..end.min(HEAD_BYTES)]);
+        true
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = format!(
+            "{}\nformat {}\nstride {}\nlength {}\nhead {:016x}\nmax {}\n",
+            MAGIC, self.format, self.stride, self.length, self.head, self.max_time
+        );
+        for entry in &self.entries {
+            let _ = writeln!(text, "{} {} {}", entry.offset, entry.max_before, entry.min_after);
+        }
+        text
+    }
+
+    // None for anything unreadable; the index is simply built again.
+    fn parse(text: &str) -> Option<TimeIndex> {
+        let mut lines = text.lines();
+        if lines.next()? != MAGIC {
+            return None;
+        }
+        let mut header = |name: &str| lines.next()?.strip_prefix(name)?.strip_prefix(' ');
+        let format = header("format")?.to_string();
+        let stride = header("stride")?.parse().ok()?;
+        let length = header("length")?.parse().ok()?;
+        let head = u64::from_str_radix(header("head")?, 16).ok()?;
+        let max_time = header("max")?.parse().ok()?;
+
+        let entries = lines
+            .map(|line| {
+                let mut numbers = line.split(' ');
+                let entry = Entry {
+                    offset: numbers.next()?.parse().ok()?,
+                    max_before: numbers.next()?.parse().ok()?,
+                    min_after: numbers.next()?.parse().ok()?,
+                };
+                numbers.next().is_none().then_some(entry)
+            })
+            .collect::<Option<Vec<Entry>>>()?;
+
+        Some(TimeIndex {
+            format,
+            stride,
+            length,
+            head,
+            max_time,
+            entries,
+        })
+    }
+}
+
+fn line_time(line: &[u8], format: &dyn LogFormat) -> Option<i64> {
+    let line = std::str::from_utf8(line).ok()?.trim_end_matches(['\n', '\r']);
+    millis(format.parse(line).ok()?.get_timestamp())
+}
+
+// FNV-1a; stable across runs and Rust versions, unlike `DefaultHasher`.
+fn fingerprint(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+// -----------------------------
+// Tests
+// -----------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Simple;
+
+    const STRIDE: u64 = 120;
+
+    // Past 10:59 too, for ranges that end after the last line.
+    fn time(minute: u32) -> i64 {
+        millis("2023-01-01 10:00:00").unwrap() + i64::from(minute) * 60_000
+    }
+
+    // Mostly in order, with lines written late or early and lines without
+    // a time; `None` is a continuation line.
+    fn log(minutes: &[Option<u32>]) -> String {
+        minutes
+            .iter()
+            .map(|minute| match minute {
+                Some(m) => format!("2023-01-01 10:{:02}:00 INFO: event at minute {}\n", m, m),
+                None => "    at some.frame(File.java:12)\n".to_string(),
+            })
+            .collect()
+    }
+
+    fn minutes() -> Vec<Option<u32>> {
+        (0..60)
+            .map(|i| match i % 11 {
+                3 => Some((i + 25) % 60),
+                7 => Some(i / 3),
+                9 => None,
+                _ => Some(i),
+            })
+            .collect()
+    }
+
+    fn build(data: &[u8]) -> TimeIndex {
+        let mut index = TimeIndex::empty(&Simple, STRIDE);
+        index.scan(data, &Simple);
+        index
+    }
+
+    // Every line timed in `from..to` lies inside the region.
+    fn assert_covers(index: &TimeIndex, data: &[u8]) {
+        for from in (0..62).step_by(3) {
+            for to in from..62 {
+                let (start, end) = index.region(time(from), time(to), data, &Simple);
+                let mut offset = 0;
+                for line in data.split_inclusive(|&b| b == b'\n') {
+                    let line_start = offset;
+                    offset += line.len();
+                    let Some(t) = line_time(line, &Simple) else {
+                        continue;
+                    };
+                    if (time(from)..time(to)).contains(&t) {
+                        assert!(
+                            start <= line_start && offset <= end,
+                            "{}..{}: line at {} outside {}..{}",
+                            from,
+                            to,
+                            line_start,
+                            start,
+                            end
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn region_covers_out_of_order_lines() {
+        let text = log(&minutes());
+        let index = build(text.as_bytes());
+        assert!(index.entries() > 10);
+        assert_covers(&index, text.as_bytes());
+
+        // Out-of-order lines only widen the region; it still skips some.
+        let (start, end) = index.region(time(40), time(41), text.as_bytes(), &Simple);
+        assert!(start > 0, "{}..{}", start, end);
+    }
+
+    #[test]
+    fn region_is_narrow_for_ordered_lines() {
+        let text = log(&(0..60).map(Some).collect::<Vec<_>>());
+        let index = build(text.as_bytes());
+        assert_covers(&index, text.as_bytes());
+        let (start, end) = index.region(time(30), time(31), text.as_bytes(), &Simple);
+        assert!(end - start <= 3 * STRIDE as usize, "{}..{}", start, end);
+    }
+
+    #[test]
+    fn region_covers_a_last_line_without_a_break() {
+        let mut text = log(&minutes());
+        text.push_str("2023-01-01 10:00:30 INFO: late and unfinished");
+        let index = build(text.as_bytes());
+        assert_eq!(index.length as usize, text.rfind('\n').unwrap() + 1);
+        assert_covers(&index, text.as_bytes());
+        let (start, end) = index.region(time(0), time(1), text.as_bytes(), &Simple);
+        assert_eq!((start, end), (0, text.len()));
+    }
+
+    #[test]
+    fn extending_matches_building_at_once() {
+        let text = log(&minutes());
+        let cut = text[..text.len() / 2].rfind('\n').unwrap() + 1;
+
+        let mut index = build(&text.as_bytes()[..cut]);
+        assert!(index.scan(text.as_bytes(), &Simple));
+        assert!(!index.scan(text.as_bytes(), &Simple));
+        assert_eq!(index.to_text(), build(text.as_bytes()).to_text());
+        assert_covers(&index, text.as_bytes());
+    }
+
+    #[test]
+    fn text_round_trips() {
+        let text = log(&minutes());
+        let index = build(text.as_bytes());
+        let saved = index.to_text();
+        let loaded = TimeIndex::parse(&saved).unwrap();
+        assert_eq!(loaded.entries, index.entries);
+        assert_eq!(loaded.to_text(), saved);
+
+        assert!(TimeIndex::parse("logparser-index 2\n").is_none());
+        assert!(TimeIndex::parse(&saved.replace("stride", "stripe")).is_none());
+        assert!(TimeIndex::parse(&format!("{}1 2 3 4\n", saved)).is_none());
+        assert!(TimeIndex::parse(&format!("{}1 2\n", saved)).is_none());
+    }
+
+    #[test]
+    fn fits_only_the_same_log() {
+        let text = log(&minutes());
+        let data = text.as_bytes();
+        let index = build(data);
+        assert!(index.fits(data, &Simple, STRIDE));
+
+        let grown = format!("{}{}", text, log(&[Some(59)]));
+        assert!(index.fits(grown.as_bytes(), &Simple, STRIDE));
+
+        // Truncated, replaced with the same length, or read differently.
+        assert!(!index.fits(&data[..data.len() - 1], &Simple, STRIDE));
+        let replaced = text.replacen("10:00:00", "11:00:00", 1);
+        assert_eq!(replaced.len(), text.len());
+        assert!(!index.fits(replaced.as_bytes(), &Simple, STRIDE));
+        assert!(!index.fits(data, &Simple, STRIDE * 2));
+        assert!(!index.fits(data, &crate::format::Logfmt, STRIDE));
+    }
+}
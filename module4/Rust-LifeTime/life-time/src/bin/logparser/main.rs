@@ -0,0 +1,660 @@
+// Log parser with lifetimes - real-world example
+//
+// Usage: logparser [path] [--format auto|simple|syslog|json|access|logfmt]
+//                  [--follow] [--from-end] [--state file] [--async]
+//                  [--query expr] [--output table|csv|json]
+//                  [--multiline off|indented] [--record-start regex]
+//                  [--from time] [--to time] [--index-stride bytes]
+// Without a path the built-in sample is parsed, followed by one line in
+// each supported format. The default `auto` picks the format from the
+// first lines of the input (see format.rs). `--follow` keeps printing
+// lines as they are appended, across rotation and truncation (see
+// follow.rs); `--from-end` skips what is already there, `--state` saves
+// the position after every batch and resumes from it on the next run, and
+// `--async` follows on a tokio runtime instead of a blocking loop. Each
+// of them implies `--follow`. `--query` filters and aggregates the records
+// instead of listing them all (see query.rs), e.g.
+//   --query 'level=ERROR and message~"timeout" | count by level, minute'
+// When following, only filters are allowed. `--multiline indented` joins
+// lines starting with whitespace to the record before them, `--record-start`
+// joins every line not matching the pattern (see multiline.rs); when
+// following, the last record is held back until the next one starts or no
+// line has arrived for a second. `--from` and
+// `--to` keep the records timed within that range, `--to` excluded; for a
+// file they go through a time index stored beside it as `<path>.idx`, so
+// only the part of the file around the range is read (see index.rs).
+// `--index-stride` sets how many bytes one index entry covers.
+
+mod follow;
+mod format;
+mod index;
+mod multiline;
+mod query;
+
+use follow::{Batch, Checkpoint, Follower, Start};
+use format::LogFormat;
+use index::{Freshness, TimeIndex};
+use memmap2::Mmap;
+use multiline::Multiline;
+use query::{Output, Query};
+use regex::Regex;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+use std::mem;
+use std::process;
+use std::time::Duration;
+
+pub struct LogParser<'a> {
+    log_line: &'a str,
+    timestamp: &'a str,
+    message: &'a str,
+    log_level: &'a str,
+    // Everything else the format knows, e.g. host, status or request_id.
+    fields: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> LogParser<'a> {
+    fn new(log_line: &'a str, format: &dyn LogFormat) -> Result<Self, &'static str> {
+        // A stitched record: the format reads the first line and the other
+        // lines continue the message, if the message is last on that line.
+        let Some((first, continuation)) = log_line.split_once('\n') else {
+            return format.parse(log_line);
+        };
+        let first = first.strip_suffix('\r').unwrap_or(first);
+        let mut log = format.parse(first)?;
+        log.log_line = log_line;
+
+        let line_start = first.as_ptr() as usize;
+        let line_end = line_start + first.len();
+        let message_start = log.message.as_ptr() as usize;
+        if (line_start..=line_end).contains(&message_start)
+            && message_start + log.message.len() == line_end
+        {
+            log.message = &log_line[message_start - line_start..];
+        } else {
+            log.fields.push(("continuation", continuation));
+        }
+        Ok(log)
+    }
+    
+    fn get_timestamp(&self) -> &'a str {
+        self.timestamp
+    }
+    
+    fn get_message(&self) -> &'a str {
+        self.message
+    }
+    
+    fn get_log_level(&self) -> &'a str {
+        self.log_level
+    }
+    
+    fn get_full_log(&self) -> &'a str {
+        self.log_line
+    }
+
+    fn get_field(&self, name: &str) -> Option<&'a str> {
+        self.fields
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| *value)
+    }
+    
+    fn combine_with_other_log<'b>(
+        &self,
+        other: &'b LogParser<'b>
+    ) -> String {
+        format!("[{}] {} -- [{}] {}", 
+                self.get_log_level(), 
+                self.get_message(),
+                other.get_log_level(),
+                other.get_message())
+    }
+    
+    fn display(&self) {
+        println!("Full: {}", self.get_full_log());
+        println!("  Time: {}", self.get_timestamp());
+        println!("  Level: {}", self.get_log_level());
+        println!("  Message: {}", self.get_message());
+        for (key, value) in &self.fields {
+            println!("  {}: {}", key, value);
+        }
+    }
+}
+
+// The records of `log_data` that parse, within `--from`/`--to` if given.
+fn load_logs<'a>(log_data: &'a str, format: &dyn LogFormat, options: &Options) -> Vec<LogParser<'a>> {
+    options
+        .multiline
+        .records(log_data)
+        .filter_map(|line| LogParser::new(line, format).ok())
+        .filter(|log| options.in_range(log))
+        .collect()
+}
+
+fn process_logs<'a>(log_data: &'a str, format: &dyn LogFormat, options: &Options) -> Vec<LogParser<'a>> {
+    let logs = load_logs(log_data, format, options);
+    
+    println!("=== Processing {} log entries ===", logs.len());
+    
+    for (i, log) in logs.iter().enumerate() {
+        println!("\nEntry {}:", i + 1);
+        log.display();
+    }
+    
+    logs
+}
+
+fn analyze_logs(logs: &[LogParser<'_>]) {
+    println!("\n=== Log Analysis ===");
+    
+    // Count by log level
+    let mut error_count = 0;
+    let mut warning_count = 0;
+    let mut info_count = 0;
+    
+    for log in logs {
+        match log.get_log_level() {
+            "ERROR" => error_count += 1,
+            "WARN" => warning_count += 1,
+            "INFO" => info_count += 1,
+            _ => {}
+        }
+    }
+    
+    println!("ERROR logs: {}", error_count);
+    println!("WARN logs: {}", warning_count);
+    println!("INFO logs: {}", info_count);
+    
+    // Find longest message
+    if let Some(longest) = logs.iter().max_by_key(|log| log.get_message().len()) {
+        println!("\nLongest message ({} chars):", longest.get_message().len());
+        println!("  '{}'", longest.get_message());
+    }
+}
+
+// ------------------ FOLLOW MODE ------------------
+fn open_follower(options: &Options) -> io::Result<Follower> {
+    let path = options.path.as_deref().expect("checked in parse_args");
+    let start = match options.state.as_deref().map(|state| Checkpoint::load(Path::new(state))) {
+        Some(Ok(checkpoint)) => Start::Resume(checkpoint),
+        // No state yet: first run.
+        Some(Err(err)) if err.kind() == io::ErrorKind::NotFound && options.from_end => Start::End,
+        Some(Err(err)) if err.kind() == io::ErrorKind::NotFound => Start::Beginning,
+        Some(Err(err)) => return Err(err),
+        None if options.from_end => Start::End,
+        None => Start::Beginning,
+    };
+    Follower::open(path, start)
+        .map_err(|err| io::Error::new(err.kind(), format!("Cannot follow {}: {}", path, err)))
+}
+
+// How long a held-back record waits for more of its lines.
+const HOLD_BACK: Duration = Duration::from_secs(1);
+
+// The format is detected from the first batch if none was given. When
+// records span lines, the last record of a batch may go on in the next
+// one, so it is kept in `pending` until a later batch starts another
+// record or nothing arrives for `HOLD_BACK`. The position is saved only
+// after the batch is printed, and before the held-back record.
+fn print_batch(batch: Batch<'_>, options: &mut Options, pending: &mut String) -> io::Result<()> {
+    let joined;
+    let text = if pending.is_empty() {
+        batch.text
+    } else {
+        joined = mem::take(pending) + batch.text;
+        joined.as_str()
+    };
+    let format = options.format.get_or_insert_with(|| choose_format(None, text, true));
+
+    let mut records: Vec<&str> = options.multiline.records(text).collect();
+    let held = match options.multiline {
+        Multiline::Off => None,
+        // Nothing new for a while: the last record is complete.
+        _ if batch.text.is_empty() => None,
+        _ => records.pop(),
+    };
+    let logs = records
+        .into_iter()
+        .filter_map(|record| LogParser::new(record, format.as_ref()).ok())
+        .filter(|log| options.query.as_ref().is_none_or(|query| query.matches(log)));
+    for log in logs {
+        println!();
+        log.display();
+    }
+    // Piped output must not lag behind the saved position.
+    io::stdout().flush()?;
+
+    if let Some(record) = held {
+        let start = record.as_ptr() as usize - text.as_ptr() as usize;
+        *pending = text[start..].to_string();
+    }
+    // A record held back across a rotation cannot be placed exactly; an
+    // earlier position only prints some lines again after a restart.
+    let checkpoint = batch
+        .checkpoint
+        .offset
+        .checked_sub(pending.len() as u64)
+        .map(|offset| Checkpoint { offset, ..batch.checkpoint });
+    match (&options.state, checkpoint) {
+        (Some(state), Some(checkpoint)) => checkpoint.save(Path::new(state)),
+        _ => Ok(()),
+    }
+}
+
+fn follow(mut options: Options) -> io::Result<()> {
+    let mut follower = open_follower(&options)?;
+    let mut pending = String::new();
+    loop {
+        let timeout = (!pending.is_empty()).then_some(HOLD_BACK);
+        print_batch(follower.next_batch(timeout)?, &mut options, &mut pending)?;
+    }
+}
+
+async fn follow_async(mut options: Options) -> io::Result<()> {
+    let mut follower = open_follower(&options)?;
+    let mut pending = String::new();
+    loop {
+        let timeout = (!pending.is_empty()).then_some(HOLD_BACK);
+        print_batch(follower.next_batch_async(timeout).await?, &mut options, &mut pending)?;
+    }
+}
+
+// ------------------ QUERY ------------------
+fn run_query(mut options: Options, log_data: &str) {
+    let format = choose_format(options.format.take(), log_data, false);
+    let logs = load_logs(log_data, format.as_ref(), &options);
+    let Some(query) = &options.query else { return };
+
+    let table = query.run(&logs);
+    if let Err(err) = table.write(options.output, &mut io::stdout().lock()) {
+        eprintln!("Cannot write the result: {}", err);
+        process::exit(1);
+    }
+}
+
+// ------------------ TIME RANGE ------------------
+fn map_file(path: &str) -> io::Result<Mmap> {
+    let file = File::open(path)?;
+    // SAFETY: the map is only read. A log truncated while mapped can fault
+    // on access (SIGBUS); the time index is meant for logs that only grow.
+    unsafe { Mmap::map(&file) }
+}
+
+// The part of the mapped log that may hold records in range, via the time
+// index. Sets the format if it was to be detected.
+fn indexed_region<'m>(options: &mut Options, path: &str, data: &'m [u8]) -> Result<&'m str, String> {
+    if options.format.is_none() {
+        // Whole lines from the start, up to what detection looks at.
+        let head = &data[..data.len().min(64 * 1024)];
+        let head = &head[..head.iter().rposition(|&b| b == b'\n').map_or(head.len(), |i| i + 1)];
+        let head = match std::str::from_utf8(head) {
+            Ok(head) => head,
+            Err(err) => std::str::from_utf8(&head[..err.valid_up_to()]).expect("valid prefix"),
+        };
+        options.format = Some(choose_format(None, head, options.query.is_none()));
+    }
+    let format = options.format.as_deref().expect("set above");
+
+    let (time_index, freshness) = TimeIndex::open(Path::new(path), data, format, options.index_stride)
+        .map_err(|err| format!("Cannot open the index of {}: {}", path, err))?;
+    let (start, end) = time_index.region(
+        options.from.unwrap_or(i64::MIN),
+        options.to.unwrap_or(i64::MAX),
+        data,
+        format,
+    );
+    let state = match freshness {
+        Freshness::Loaded => "used",
+        Freshness::Extended => "updated",
+        Freshness::Built => "built",
+    };
+    eprintln!(
+        "Index {} ({} entries); scanning bytes {}..{} of {}",
+        state,
+        time_index.entries(),
+        start,
+        end,
+        data.len()
+    );
+    std::str::from_utf8(&data[start..end]).map_err(|err| format!("{} is not UTF-8: {}", path, err))
+}
+
+struct Options {
+    path: Option<String>,
+    // None means auto-detect.
+    format: Option<Box<dyn LogFormat>>,
+    follow: bool,
+    from_end: bool,
+    state: Option<String>,
+    use_async: bool,
+    query: Option<Query>,
+    output: Output,
+    multiline: Multiline,
+    // Milliseconds since the epoch; `to` is excluded.
+    from: Option<i64>,
+    to: Option<i64>,
+    index_stride: u64,
+}
+
+impl Options {
+    fn has_range(&self) -> bool {
+        self.from.is_some() || self.to.is_some()
+    }
+
+    fn in_range(&self, log: &LogParser<'_>) -> bool {
+        if !self.has_range() {
+            return true;
+        }
+        index::millis(log.get_timestamp()).is_some_and(|time| {
+            self.from.is_none_or(|from| time >= from) && self.to.is_none_or(|to| time < to)
+        })
+    }
+}
+
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        path: None,
+        format: None,
+        follow: false,
+        from_end: false,
+        state: None,
+        use_async: false,
+        query: None,
+        output: Output::Table,
+        multiline: Multiline::Off,
+        from: None,
+        to: None,
+        index_stride: index::DEFAULT_STRIDE,
+    };
+    let mut args = args.peekable();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                let name = args.next().ok_or("--format needs a name")?;
+                if name != "auto" {
+                    let names: Vec<&str> = format::all().iter().map(|f| f.name()).collect();
+                    let format = format::by_name(&name).ok_or_else(|| {
+                        format!("unknown format '{}' (use auto, {})", name, names.join(", "))
+                    })?;
+                    options.format = Some(format);
+                }
+            }
+            "--follow" => options.follow = true,
+            "--from-end" => {
+                options.from_end = true;
+                options.follow = true;
+            }
+            "--state" => {
+                options.state = Some(args.next().ok_or("--state needs a path")?);
+                options.follow = true;
+            }
+            "--async" => {
+                options.use_async = true;
+                options.follow = true;
+            }
+            "--query" => {
+                let query = args.next().ok_or("--query needs an expression")?;
+                options.query = Some(query.parse().map_err(|err| format!("--query: {}", err))?);
+            }
+            "--output" => options.output = args.next().ok_or("--output needs a value")?.parse()?,
+            "--multiline" => {
+                options.multiline = match args.next().as_deref() {
+                    Some("off") => Multiline::Off,
+                    Some("indented") => Multiline::Indented,
+                    _ => return Err("--multiline expects off or indented".to_string()),
+                }
+            }
+            "--record-start" => {
+                let pattern = args.next().ok_or("--record-start needs a regex")?;
+                let pattern = Regex::new(&pattern).map_err(|err| format!("--record-start: {}", err))?;
+                options.multiline = Multiline::Start(pattern);
+            }
+            "--from" | "--to" => {
+                let value = args.next().ok_or_else(|| format!("{} needs a time", arg))?;
+                let time = index::millis(&value).ok_or_else(|| format!("{}: '{}' is not a time", arg, value))?;
+                if arg == "--from" {
+                    options.from = Some(time);
+                } else {
+                    options.to = Some(time);
+                }
+            }
+            "--index-stride" => {
+                options.index_stride = args
+                    .next()
+                    .and_then(|bytes| bytes.parse().ok())
+                    .filter(|&bytes| bytes > 0)
+                    .ok_or("--index-stride expects a positive number of bytes")?;
+            }
+            flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+            _ if options.path.is_none() => options.path = Some(arg),
+            _ => return Err(format!("unexpected argument '{}'", arg)),
+        }
+    }
+
+    if options.follow && options.path.is_none() {
+        return Err("--follow needs a path".to_string());
+    }
+    if options.follow && options.query.as_ref().is_some_and(|query| !query.is_filter()) {
+        return Err("--follow only takes filter queries, without | stages".to_string());
+    }
+    if options.follow && options.has_range() {
+        return Err("--from and --to cannot be combined with --follow".to_string());
+    }
+    Ok(options)
+}
+
+// Not `chatty` when stdout is the query result.
+fn choose_format(format: Option<Box<dyn LogFormat>>, log_data: &str, chatty: bool) -> Box<dyn LogFormat> {
+    if let Some(format) = format {
+        return format;
+    }
+    match format::detect(log_data) {
+        Some(format) => {
+            if chatty {
+                println!("Detected format: {}", format.name());
+            }
+            format
+        }
+        None => {
+            eprintln!("Cannot detect the log format; choose one with --format");
+            process::exit(1);
+        }
+    }
+}
+
+fn main() {
+    let mut options = parse_args(env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        process::exit(2);
+    });
+
+    if options.follow {
+        let result = if options.use_async {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_time()
+                .build()
+                .and_then(|runtime| runtime.block_on(follow_async(options)))
+        } else {
+            follow(options)
+        };
+        if let Err(err) = result {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(path) = options.path.clone()
+        && options.has_range()
+    {
+        let map = map_file(&path).unwrap_or_else(|err| {
+            eprintln!("Cannot read {}: {}", path, err);
+            process::exit(1);
+        });
+        let log_data = indexed_region(&mut options, &path, &map).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            process::exit(1);
+        });
+        if options.query.is_some() {
+            run_query(options, log_data);
+            return;
+        }
+        let format = options.format.take().expect("set by indexed_region");
+        let logs = process_logs(log_data, format.as_ref(), &options);
+        analyze_logs(&logs);
+        return;
+    }
+
+    if let Some(path) = options.path.clone() {
+        let log_data = fs::read_to_string(&path).unwrap_or_else(|err| {
+            eprintln!("Cannot read {}: {}", path, err);
+            process::exit(1);
+        });
+        if options.query.is_some() {
+            run_query(options, &log_data);
+            return;
+        }
+        let format = choose_format(options.format.take(), &log_data, true);
+        let logs = process_logs(&log_data, format.as_ref(), &options);
+        analyze_logs(&logs);
+        return;
+    }
+
+    let log_data = String::from(
+        "2023-01-01 10:30:00 INFO: Server started successfully\n\
+         2023-01-01 10:31:00 WARN: High memory usage detected\n\
+         2023-01-01 10:32:00 ERROR: Database connection failed\n\
+         2023-01-01 10:33:00 INFO: Retrying connection...\n\
+         2023-01-01 10:34:00 WARN: Disk space running low\n\
+         2023-01-01 10:35:00 INFO: Backup completed"
+    );
+    
+    if options.query.is_some() {
+        run_query(options, &log_data);
+        return;
+    }
+
+    println!("=== LOG PARSER WITH LIFETIMES ===\n");
+
+    // Parse logs
+    let format = choose_format(options.format.take(), &log_data, true);
+    let logs = process_logs(&log_data, format.as_ref(), &options);
+    
+    // Analyze logs
+    analyze_logs(&logs);
+    
+    // Demonstrate combine method
+    if logs.len() >= 2 {
+        println!("\n=== Combined Log Example ===");
+        let combined = logs[0].combine_with_other_log(&logs[1]);
+        println!("Combined: {}", combined);
+    }
+    
+    // The same record shape from every supported format
+    println!("\n=== Other Formats ===");
+    let samples = [
+        "<165>1 2023-01-01T10:36:00.003Z web01 checkout 4321 ID47 [origin ip=\"10.0.0.7\"] Payment declined",
+        r#"{"ts":"2023-01-01T10:37:00Z","level":"warn","msg":"Slow query","duration_ms":1840,"tags":["db","orders"]}"#,
+        r#"127.0.0.1 - frank [01/Jan/2023:10:38:00 +0000] "GET /api/orders HTTP/1.1" 503 512 "-" "curl/8.0""#,
+        r#"time=2023-01-01T10:39:00Z level=error msg="cache miss storm" request_id=abc123"#,
+    ];
+    for sample in samples {
+        let Some(format) = format::detect(sample) else {
+            println!("\nNo format matches: {}", sample);
+            continue;
+        };
+        if let Ok(log) = LogParser::new(sample, format.as_ref()) {
+            println!("\n[{}]", format.name());
+            log.display();
+            if let Some(status) = log.get_field("status") {
+                println!("  (HTTP status {})", status);
+            }
+        }
+    }
+
+    // Show that references depend on original data
+    println!("\n=== Lifetime Relationship ===");
+    println!("All LogParser instances borrow from the original log_data.");
+    println!("They cannot outlive the log_data string.");
+}
+// -----------------------------
+// Tests
+// -----------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use format::{JsonLines, Simple};
+
+    #[test]
+    fn stitched_record_extends_a_trailing_message() {
+        let record = "2023-01-01 10:32:00 ERROR: Request failed\r\n\
+                      java.lang.IllegalStateException\n    at Pool.take(Pool.java:42)";
+        let log = LogParser::new(record, &Simple).unwrap();
+        assert_eq!(log.get_timestamp(), "2023-01-01 10:32:00");
+        assert_eq!(
+            log.get_message(),
+            "Request failed\r\njava.lang.IllegalStateException\n    at Pool.take(Pool.java:42)"
+        );
+        assert_eq!(log.get_full_log(), record);
+        assert_eq!(log.get_field("continuation"), None);
+    }
+
+    #[test]
+    fn stitched_record_keeps_a_quoted_message() {
+        let record = "{\"time\": \"2023-01-01T10:32:00Z\", \"msg\": \"failed\"}\n  at Pool.take";
+        let log = LogParser::new(record, &JsonLines).unwrap();
+        assert_eq!(log.get_message(), "failed");
+        assert_eq!(log.get_field("continuation"), Some("  at Pool.take"));
+        assert_eq!(log.get_full_log(), record);
+
+        assert!(LogParser::new("not a record\n  at x", &Simple).is_err());
+    }
+
+    fn options(state: &Path, multiline: &str) -> Options {
+        let args = ["app.log", "--format", "simple", "--multiline", multiline, "--state"];
+        let args = args.iter().map(|arg| arg.to_string());
+        parse_args(args.chain([state.display().to_string()])).unwrap()
+    }
+
+    fn batch(text: &str, offset: u64) -> Batch<'_> {
+        Batch {
+            text,
+            checkpoint: Checkpoint { file_id: None, offset },
+        }
+    }
+
+    #[test]
+    fn holds_back_the_last_record_until_it_is_complete() {
+        let state = env::temp_dir().join(format!("logparser-main-{}.state", process::id()));
+        let saved = || Checkpoint::load(&state).unwrap().offset;
+        let mut options = options(&state, "indented");
+        let mut pending = String::new();
+
+        let first = "2023-01-01 10:00:00 INFO: a\n2023-01-01 10:00:01 ERROR: b\n  at x\n";
+        print_batch(batch(first, 100), &mut options, &mut pending).unwrap();
+        assert_eq!(pending, "2023-01-01 10:00:01 ERROR: b\n  at x\n");
+        assert_eq!(saved(), 100 - pending.len() as u64);
+
+        // More of the held record, then the start of the next one.
+        let second = "  at y\n2023-01-01 10:00:02 INFO: c\n";
+        print_batch(batch(second, 140), &mut options, &mut pending).unwrap();
+        assert_eq!(pending, "2023-01-01 10:00:02 INFO: c\n");
+        assert_eq!(saved(), 140 - pending.len() as u64);
+
+        // Nothing new for a while: the held record is complete.
+        print_batch(batch("", 140), &mut options, &mut pending).unwrap();
+        assert_eq!(pending, "");
+        assert_eq!(saved(), 140);
+
+        // Without stitching nothing is held back.
+        let mut options = self::options(&state, "off");
+        print_batch(batch(first, 200), &mut options, &mut pending).unwrap();
+        assert_eq!(pending, "");
+        assert_eq!(saved(), 200);
+        fs::remove_file(&state).unwrap();
+    }
+}
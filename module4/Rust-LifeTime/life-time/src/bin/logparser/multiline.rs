@@ -0,0 +1,141 @@
+// src/bin/logparser/multiline.rs
+// Stitching multi-line records, e.g. stack traces
+//
+//     2023-01-01 10:32:00 ERROR: Request failed
+//     java.lang.IllegalStateException: pool exhausted
+//         at com.example.Pool.take(Pool.java:42)
+//     Caused by: java.net.SocketTimeoutException
+//
+// A record is one slice of the input running from its first line to its
+// last continuation line, so stitching copies nothing. Lines before the
+// first record start are records of their own.
+
+use regex::Regex;
+
+pub enum Multiline {
+    /// Every line is a record.
+    Off,
+    /// Lines starting with a space or tab continue the previous record.
+    Indented,
+    /// Lines matching the pattern start a record; all others continue one.
+    Start(Regex),
+}
+
+impl Multiline {
+    fn starts_record(&self, line: &str) -> bool {
+        match self {
+            Multiline::Off => true,
+            // A blank line is not a start; it may sit inside a record.
+            Multiline::Indented => !line.is_empty() && !line.starts_with([' ', '\t']),
+            Multiline::Start(pattern) => pattern.is_match(line),
+        }
+    }
+
+    /// Records of `text`, without their final line break. Blank lines
+    /// outside a record are skipped.
+    pub fn records<'a>(&self, text: &'a str) -> impl Iterator<Item = &'a str> {
+        let mut rest = text;
+        std::iter::from_fn(move || {
+            loop {
+                if rest.is_empty() {
+                    return None;
+                }
+                let (first, after) = split_line(rest);
+                let start = rest;
+                let mut end = first.len();
+                rest = after;
+
+                if first.trim().is_empty() {
+                    continue;
+                }
+                loop {
+                    let (line, after) = split_line(rest);
+                    if rest.is_empty() || self.starts_record(line) {
+                        break;
+                    }
+                    // Up to the end of this line, within `start`; blank
+                    // lines only count once something follows them.
+                    if !line.trim().is_empty() {
+                        end = start.len() - rest.len() + line.len();
+                    }
+                    rest = after;
+                }
+                return Some(&start[..end]);
+            }
+        })
+    }
+}
+
+// (line without its line break, everything after it)
+fn split_line(text: &str) -> (&str, &str) {
+    let (line, rest) = text.split_once('\n').unwrap_or((text, ""));
+    (line.strip_suffix('\r').unwrap_or(line), rest)
+}
+
+// -----------------------------
+// Tests
+// -----------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records<'a>(multiline: &Multiline, text: &'a str) -> Vec<&'a str> {
+        multiline.records(text).collect()
+    }
+
+    #[test]
+    fn off_gives_every_non_blank_line() {
+        assert_eq!(records(&Multiline::Off, "a\n  b\n\nc"), ["a", "  b", "c"]);
+    }
+
+    #[test]
+    fn indented_lines_continue_a_record() {
+        assert_eq!(
+            records(&Multiline::Indented, "a\n  b\n\tc\nd\n    e\n"),
+            ["a\n  b\n\tc", "d\n    e"]
+        );
+        // A record may start indented when nothing comes before it.
+        assert_eq!(records(&Multiline::Indented, "  a\nb"), ["  a", "b"]);
+    }
+
+    #[test]
+    fn record_start_pattern_begins_records() {
+        let multiline = Multiline::Start(Regex::new(r"^\d{4}-").unwrap());
+        let text = "2023-01-01 ERROR: failed\n\
+                    java.lang.IllegalStateException: pool exhausted\n    \
+                    at Pool.take(Pool.java:42)\n\
+                    2023-01-01 INFO: recovered\n";
+        assert_eq!(
+            records(&multiline, text),
+            [
+                "2023-01-01 ERROR: failed\n\
+                 java.lang.IllegalStateException: pool exhausted\n    \
+                 at Pool.take(Pool.java:42)",
+                "2023-01-01 INFO: recovered",
+            ]
+        );
+    }
+
+    #[test]
+    fn blank_lines_count_only_inside_a_record() {
+        assert_eq!(
+            records(&Multiline::Indented, "\na\n  b\n\n  c\n\n\nd\n\n"),
+            ["a\n  b\n\n  c", "d"]
+        );
+        let multiline = Multiline::Start(Regex::new("^>").unwrap());
+        assert_eq!(records(&multiline, ">a\nb\n\n \n>c\n"), [">a\nb", ">c"]);
+    }
+
+    #[test]
+    fn crlf_lines_are_matched_without_the_cr() {
+        assert_eq!(
+            records(&Multiline::Indented, "a\r\n  b\r\nc\r\n"),
+            ["a\r\n  b", "c"]
+        );
+        let multiline = Multiline::Start(Regex::new("^start$").unwrap());
+        assert_eq!(
+            records(&multiline, "start\r\nmore\r\nstart\r\n"),
+            ["start\r\nmore", "start"]
+        );
+    }
+}
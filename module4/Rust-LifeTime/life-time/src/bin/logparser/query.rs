@@ -0,0 +1,874 @@
+// src/bin/logparser/query.rs
+// Query language for filtering and aggregating parsed records
+//
+//     level=ERROR and message~"timeout" | count by level, minute | top 5
+//
+// A query is an optional filter followed by `|` stages.
+//
+// Filter: comparisons joined with `and`, `or`, `not` and parentheses.
+//   field = value, field != value   equal, not equal
+//   field ~ regex, field !~ regex   regex search, e.g. message~"time ?out"
+//   field > value, >=, <, <=        numbers compare as numbers, timestamp
+//                                   (or time) as times, the rest as text
+// Fields are level, message, timestamp/time and whatever the format
+// extracts (status, host, request_id, ...). A record without the field only
+// matches `!=` and `!~`. Values are bare words or "quoted strings".
+//
+// Stages, `count`/`top N by` first if present; with no filter the first
+// `|` may be left out:
+//   count                  number of matching records
+//   count by k1, k2        one row per distinct key combination; keys are
+//                          fields or the time buckets second, minute,
+//                          hour, day
+//   top N                  the N largest counts
+//   top N by k1, k2        short for `count by k1, k2 | top N`
+//   limit N                the first N rows
+// Without a count the matching records are listed.
+
+use crate::LogParser;
+use crate::format::parse_time;
+use chrono::NaiveDateTime;
+use regex::Regex;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+// -----------------------------
+// Tokens
+// -----------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Match,
+    NotMatch,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    // A "quoted string", never a keyword.
+    Text(String),
+    Op(Op),
+    Pipe,
+    Comma,
+    Open,
+    Close,
+}
+
+fn tokenize(query: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = query.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let next_is_eq = chars.peek().is_some_and(|(_, next)| *next == '=');
+        let next_is_tilde = chars.peek().is_some_and(|(_, next)| *next == '~');
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '|' => Token::Pipe,
+            ',' => Token::Comma,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '=' => Token::Op(Op::Eq),
+            '~' => Token::Op(Op::Match),
+            '!' if next_is_eq || next_is_tilde => {
+                chars.next();
+                Token::Op(if next_is_eq { Op::Ne } else { Op::NotMatch })
+            }
+            '>' | '<' => {
+                if next_is_eq {
+                    chars.next();
+                }
+                Token::Op(match (c, next_is_eq) {
+                    ('>', false) => Op::Gt,
+                    ('>', true) => Op::Ge,
+                    ('<', false) => Op::Lt,
+                    _ => Op::Le,
+                })
+            }
+            '"' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        None => return Err(format!("unterminated string at column {}", start + 1)),
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, c)) => text.push(c),
+                            None => return Err(format!("unterminated string at column {}", start + 1)),
+                        },
+                        Some((_, c)) => text.push(c),
+                    }
+                }
+                Token::Text(text)
+            }
+            '!' => return Err(format!("expected != or !~ at column {}", start + 1)),
+            _ => {
+                let mut end = start + c.len_utf8();
+                while let Some(&(i, c)) = chars.peek() {
+                    if c.is_whitespace() || "|,()=!~<>\"".contains(c) {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                Token::Word(query[start..end].to_string())
+            }
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+// -----------------------------
+// Query
+// -----------------------------
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Comparison),
+}
+
+struct Comparison {
+    field: String,
+    op: Op,
+    value: String,
+    // Parsed once, for the operators that need them.
+    regex: Option<Regex>,
+    number: Option<f64>,
+    time: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bucket {
+    Second,
+    Minute,
+    Hour,
+    Day,
+}
+
+impl Bucket {
+    fn name(self) -> &'static str {
+        match self {
+            Bucket::Second => "second",
+            Bucket::Minute => "minute",
+            Bucket::Hour => "hour",
+            Bucket::Day => "day",
+        }
+    }
+}
+
+enum Key {
+    Field(String),
+    Time(Bucket),
+}
+
+enum Stage {
+    Count(Vec<Key>),
+    Top(usize),
+    Limit(usize),
+}
+
+pub struct Query {
+    filter: Option<Expr>,
+    stages: Vec<Stage>,
+}
+
+impl FromStr for Query {
+    type Err = String;
+
+    fn from_str(query: &str) -> Result<Query, String> {
+        let mut parser = Parser {
+            tokens: tokenize(query)?,
+            pos: 0,
+        };
+        let mut stages = Vec::new();
+        let filter = match parser.peek() {
+            None | Some(Token::Pipe) => None,
+            // `count by level` needs no `|` in front.
+            Some(_) if parser.at_stage() => {
+                parser.stage(&mut stages)?;
+                None
+            }
+            Some(_) => Some(parser.or()?),
+        };
+
+        while parser.peek().is_some() {
+            parser.expect(&Token::Pipe, "|")?;
+            parser.stage(&mut stages)?;
+        }
+        Ok(Query { filter, stages })
+    }
+}
+
+impl Query {
+    /// Whether the query only filters, so it can run one record at a time.
+    pub fn is_filter(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    pub fn matches(&self, log: &LogParser<'_>) -> bool {
+        self.filter.as_ref().is_none_or(|filter| filter.matches(log))
+    }
+
+    pub fn run(&self, logs: &[LogParser<'_>]) -> Table {
+        let matching: Vec<&LogParser<'_>> = logs.iter().filter(|log| self.matches(log)).collect();
+
+        let mut stages = self.stages.iter().peekable();
+        let mut table = match stages.next_if(|stage| matches!(stage, Stage::Count(_))) {
+            Some(Stage::Count(keys)) => count(&matching, keys),
+            _ => list(&matching),
+        };
+        for stage in stages {
+            match stage {
+                Stage::Top(n) => {
+                    // Stable, so equal counts stay in key order.
+                    table.rows.sort_by(|a, b| b.last().cmp(&a.last()));
+                    table.rows.truncate(*n);
+                }
+                Stage::Limit(n) => table.rows.truncate(*n),
+                Stage::Count(_) => unreachable!("count is only allowed first"),
+            }
+        }
+        table
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: &Token, name: &str) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == *expected => Ok(()),
+            Some(token) => Err(format!("expected {}, found {}", name, describe(&token))),
+            None => Err(format!("expected {} at the end", name)),
+        }
+    }
+
+    // A stage name not used as a field, as in `count > 5`.
+    fn at_stage(&self) -> bool {
+        let stage = matches!(self.peek(), Some(Token::Word(word))
+            if ["count", "top", "limit"].iter().any(|stage| word.eq_ignore_ascii_case(stage)));
+        stage && !matches!(self.tokens.get(self.pos + 1), Some(Token::Op(_)))
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword));
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and()?;
+        while self.keyword("or") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.not()?;
+        while self.keyword("and") {
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr, String> {
+        if self.keyword("not") {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        if self.peek() == Some(&Token::Open) {
+            self.pos += 1;
+            let expr = self.or()?;
+            self.expect(&Token::Close, ")")?;
+            return Ok(expr);
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let field = match self.next() {
+            Some(Token::Word(field)) => field,
+            Some(token) => return Err(format!("expected a field name, found {}", describe(&token))),
+            None => return Err("expected a field name at the end".to_string()),
+        };
+        let op = match self.next() {
+            Some(Token::Op(op)) => op,
+            _ => return Err(format!("expected an operator after '{}'", field)),
+        };
+        let value = match self.next() {
+            Some(Token::Word(value) | Token::Text(value)) => value,
+            _ => return Err(format!("expected a value after '{}'", field)),
+        };
+
+        let regex = match op {
+            Op::Match | Op::NotMatch => Some(
+                Regex::new(&value).map_err(|err| format!("invalid regex for '{}': {}", field, err))?,
+            ),
+            _ => None,
+        };
+        let ordered = matches!(op, Op::Gt | Op::Ge | Op::Lt | Op::Le);
+        let time = if is_time_field(&field) && ordered {
+            Some(parse_time(&value).ok_or_else(|| format!("'{}' is not a time", value))?)
+        } else {
+            None
+        };
+        Ok(Expr::Compare(Comparison {
+            number: value.trim().parse().ok(),
+            field,
+            op,
+            value,
+            regex,
+            time,
+        }))
+    }
+
+    fn stage(&mut self, stages: &mut Vec<Stage>) -> Result<(), String> {
+        let first = stages.is_empty();
+        let word = match self.next() {
+            Some(Token::Word(word)) => word.to_ascii_lowercase(),
+            Some(token) => return Err(format!("expected a stage after |, found {}", describe(&token))),
+            None => return Err("expected a stage after |".to_string()),
+        };
+        match word.as_str() {
+            "count" if !first => return Err("count must be the first stage".to_string()),
+            "count" => {
+                let keys = if self.keyword("by") { self.keys()? } else { Vec::new() };
+                stages.push(Stage::Count(keys));
+            }
+            "top" => {
+                let n = self.number("top")?;
+                if self.keyword("by") {
+                    if !first {
+                        return Err("top N by ... must be the first stage".to_string());
+                    }
+                    stages.push(Stage::Count(self.keys()?));
+                } else if !matches!(stages.first(), Some(Stage::Count(_))) {
+                    return Err("top needs a count before it, or use top N by ...".to_string());
+                }
+                stages.push(Stage::Top(n));
+            }
+            "limit" => {
+                let n = self.number("limit")?;
+                stages.push(Stage::Limit(n));
+            }
+            _ => return Err(format!("unknown stage '{}' (use count, top or limit)", word)),
+        }
+        Ok(())
+    }
+
+    fn keys(&mut self) -> Result<Vec<Key>, String> {
+        let mut keys = Vec::new();
+        loop {
+            let key = match self.next() {
+                Some(Token::Word(word)) => word,
+                _ => return Err("expected a field or time bucket after 'by'".to_string()),
+            };
+            let lower = key.to_ascii_lowercase();
+            let bucket = [Bucket::Second, Bucket::Minute, Bucket::Hour, Bucket::Day]
+                .into_iter()
+                .find(|bucket| bucket.name() == lower);
+            keys.push(bucket.map_or(Key::Field(key), Key::Time));
+            if self.peek() != Some(&Token::Comma) {
+                return Ok(keys);
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn number(&mut self, stage: &str) -> Result<usize, String> {
+        match self.next() {
+            Some(Token::Word(word)) => word
+                .parse()
+                .map_err(|_| format!("{} expects a number, got '{}'", stage, word)),
+            _ => Err(format!("{} expects a number", stage)),
+        }
+    }
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Word(word) => format!("'{}'", word),
+        Token::Text(text) => format!("\"{}\"", text),
+        Token::Op(op) => format!("operator {:?}", op),
+        Token::Pipe => "|".to_string(),
+        Token::Comma => "','".to_string(),
+        Token::Open => "'('".to_string(),
+        Token::Close => "')'".to_string(),
+    }
+}
+
+// -----------------------------
+// Evaluation
+// -----------------------------
+fn is_time_field(name: &str) -> bool {
+    matches!(name, "timestamp" | "time")
+}
+
+fn field<'a>(log: &LogParser<'a>, name: &str) -> Option<&'a str> {
+    match name {
+        "level" => Some(log.get_log_level()),
+        "message" | "msg" => Some(log.get_message()),
+        "timestamp" | "time" => Some(log.get_timestamp()),
+        _ => log.get_field(name),
+    }
+}
+
+impl Expr {
+    fn matches(&self, log: &LogParser<'_>) -> bool {
+        match self {
+            Expr::And(a, b) => a.matches(log) && b.matches(log),
+            Expr::Or(a, b) => a.matches(log) || b.matches(log),
+            Expr::Not(expr) => !expr.matches(log),
+            Expr::Compare(comparison) => comparison.matches(log),
+        }
+    }
+}
+
+impl Comparison {
+    fn matches(&self, log: &LogParser<'_>) -> bool {
+        let Some(actual) = field(log, &self.field) else {
+            return matches!(self.op, Op::Ne | Op::NotMatch);
+        };
+        let regex = || self.regex.as_ref().expect("compiled for ~ and !~");
+        match self.op {
+            Op::Eq => actual == self.value,
+            Op::Ne => actual != self.value,
+            Op::Match => regex().is_match(actual),
+            Op::NotMatch => !regex().is_match(actual),
+            Op::Gt => self.compare(actual) == Some(Ordering::Greater),
+            Op::Ge => matches!(self.compare(actual), Some(Ordering::Greater | Ordering::Equal)),
+            Op::Lt => self.compare(actual) == Some(Ordering::Less),
+            Op::Le => matches!(self.compare(actual), Some(Ordering::Less | Ordering::Equal)),
+        }
+    }
+
+    // None if the record's value is not comparable, e.g. an unreadable time.
+    fn compare(&self, actual: &str) -> Option<Ordering> {
+        if let Some(time) = self.time {
+            return Some(parse_time(actual)?.cmp(&time));
+        }
+        match (actual.trim().parse::<f64>(), self.number) {
+            (Ok(actual), Some(number)) => actual.partial_cmp(&number),
+            _ => Some(actual.cmp(self.value.as_str())),
+        }
+    }
+}
+
+fn bucket(log: &LogParser<'_>, bucket: Bucket) -> String {
+    let Some(time) = parse_time(log.get_timestamp()) else {
+        return "-".to_string();
+    };
+    let layout = match bucket {
+        Bucket::Second => "%Y-%m-%d %H:%M:%S",
+        Bucket::Minute => "%Y-%m-%d %H:%M",
+        Bucket::Hour => "%Y-%m-%d %H:00",
+        Bucket::Day => "%Y-%m-%d",
+    };
+    time.format(layout).to_string()
+}
+
+fn count(logs: &[&LogParser<'_>], keys: &[Key]) -> Table {
+    let mut groups: BTreeMap<Vec<String>, u64> = BTreeMap::new();
+    for log in logs {
+        let group = keys
+            .iter()
+            .map(|key| match key {
+                Key::Field(name) => field(log, name).unwrap_or("-").to_string(),
+                Key::Time(b) => bucket(log, *b),
+            })
+            .collect();
+        *groups.entry(group).or_default() += 1;
+    }
+
+    let mut columns: Vec<String> = keys
+        .iter()
+        .map(|key| match key {
+            Key::Field(name) => name.clone(),
+            Key::Time(b) => b.name().to_string(),
+        })
+        .collect();
+    columns.push("count".to_string());
+
+    // `count` without keys still answers, even with nothing matching.
+    if keys.is_empty() && groups.is_empty() {
+        groups.insert(Vec::new(), 0);
+    }
+    let rows = groups
+        .into_iter()
+        .map(|(group, n)| {
+            let mut row: Vec<Cell> = group.into_iter().map(Cell::Text).collect();
+            row.push(Cell::Number(n));
+            row
+        })
+        .collect();
+    Table { columns, rows }
+}
+
+fn list(logs: &[&LogParser<'_>]) -> Table {
+    Table {
+        columns: vec!["timestamp".into(), "level".into(), "message".into()],
+        rows: logs
+            .iter()
+            .map(|log| {
+                vec![
+                    Cell::Text(log.get_timestamp().to_string()),
+                    Cell::Text(log.get_log_level().to_string()),
+                    Cell::Text(log.get_message().to_string()),
+                ]
+            })
+            .collect(),
+    }
+}
+
+// -----------------------------
+// Output
+// -----------------------------
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Cell {
+    Text(String),
+    Number(u64),
+}
+
+impl std::fmt::Display for Cell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Cell::Text(text) => write!(f, "{}", text),
+            Cell::Number(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+pub struct Table {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Cell>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Output {
+    Table,
+    Csv,
+    Json,
+}
+
+impl FromStr for Output {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Output, String> {
+        match s {
+            "table" => Ok(Output::Table),
+            "csv" => Ok(Output::Csv),
+            "json" => Ok(Output::Json),
+            _ => Err(format!("--output expects table, csv or json, got '{}'", s)),
+        }
+    }
+}
+
+impl Table {
+    pub fn write(&self, output: Output, out: &mut impl Write) -> io::Result<()> {
+        match output {
+            Output::Table => self.write_table(out),
+            Output::Csv => {
+                let mut writer = csv::Writer::from_writer(out);
+                writer.write_record(&self.columns)?;
+                for row in &self.rows {
+                    writer.write_record(row.iter().map(|cell| cell.to_string()))?;
+                }
+                writer.flush()
+            }
+            Output::Json => {
+                // Written by hand to keep the column order.
+                writeln!(out, "[")?;
+                for (i, row) in self.rows.iter().enumerate() {
+                    let fields: Vec<String> = self
+                        .columns
+                        .iter()
+                        .zip(row)
+                        .map(|(column, cell)| {
+                            let value = match cell {
+                                Cell::Text(text) => serde_json::Value::from(text.as_str()),
+                                Cell::Number(n) => serde_json::Value::from(*n),
+                            };
+                            format!("{}: {}", serde_json::Value::from(column.as_str()), value)
+                        })
+                        .collect();
+                    let comma = if i + 1 < self.rows.len() { "," } else { "" };
+                    writeln!(out, "  {{{}}}{}", fields.join(", "), comma)?;
+                }
+                writeln!(out, "]")
+            }
+        }
+    }
+
+    fn write_table(&self, out: &mut impl Write) -> io::Result<()> {
+        // One line per row: line breaks in stitched messages are shown as \n.
+        let rendered: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| cell.to_string().replace("\r\n", "\\n").replace('\n', "\\n"))
+                    .collect()
+            })
+            .collect();
+        let widths: Vec<usize> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                rendered
+                    .iter()
+                    .map(|row| row[i].chars().count())
+                    .chain([column.chars().count()])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let header: Vec<String> = self
+            .columns
+            .iter()
+            .zip(&widths)
+            .map(|(column, width)| format!("{:<width$}", column, width = width))
+            .collect();
+        writeln!(out, "{}", header.join("  ").trim_end())?;
+        let rule: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+        writeln!(out, "{}", rule.join("  "))?;
+
+        for (row, texts) in self.rows.iter().zip(&rendered) {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(texts)
+                .zip(&widths)
+                .map(|((cell, text), width)| match cell {
+                    Cell::Number(_) => format!("{:>width$}", text, width = width),
+                    Cell::Text(_) => format!("{:<width$}", text, width = width),
+                })
+                .collect();
+            writeln!(out, "{}", cells.join("  ").trim_end())?;
+        }
+        match self.rows.len() {
+            1 => writeln!(out, "(1 row)"),
+            n => writeln!(out, "({} rows)", n),
+        }
+    }
+}
+
+// -----------------------------
+// Tests
+// -----------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::{LogFormat, Logfmt};
+
+    const LINES: [&str; 4] = [
+        "time=2023-01-01T10:00:05Z level=info msg=started status=200 host=web1",
+        "time=2023-01-01T10:00:40Z level=error msg=\"db timeout\" status=503 host=web2",
+        "time=2023-01-01T10:01:10Z level=warn msg=slow status=404 host=web1",
+        "time=2023-01-01T10:02:00Z level=error msg=\"timeout again\" status=1000",
+    ];
+
+    fn logs() -> Vec<LogParser<'static>> {
+        LINES.iter().map(|line| Logfmt.parse(line).unwrap()).collect()
+    }
+
+    fn run(query: &str) -> Vec<Vec<String>> {
+        let query: Query = query.parse().unwrap();
+        let table = query.run(&logs());
+        table
+            .rows
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+            .collect()
+    }
+
+    // Messages of the matching records.
+    fn messages(query: &str) -> Vec<String> {
+        run(query).into_iter().map(|row| row[2].clone()).collect()
+    }
+
+    fn error(query: &str) -> String {
+        query.parse::<Query>().err().expect("query should not parse")
+    }
+
+    #[test]
+    fn tokenizes_operators_and_strings() {
+        let word = |w: &str| Token::Word(w.to_string());
+        assert_eq!(
+            tokenize(r#"a!=1 b!~x c>=2 d<3 (msg~"say \"hi\"") | count by a, b"#).unwrap(),
+            vec![
+                word("a"),
+                Token::Op(Op::Ne),
+                word("1"),
+                word("b"),
+                Token::Op(Op::NotMatch),
+                word("x"),
+                word("c"),
+                Token::Op(Op::Ge),
+                word("2"),
+                word("d"),
+                Token::Op(Op::Lt),
+                word("3"),
+                Token::Open,
+                word("msg"),
+                Token::Op(Op::Match),
+                Token::Text("say \"hi\"".to_string()),
+                Token::Close,
+                Token::Pipe,
+                word("count"),
+                word("by"),
+                word("a"),
+                Token::Comma,
+                word("b"),
+            ]
+        );
+        assert_eq!(tokenize("msg=\"open").unwrap_err(), "unterminated string at column 5");
+        assert_eq!(tokenize("level ! ERROR").unwrap_err(), "expected != or !~ at column 7");
+    }
+
+    #[test]
+    fn not_binds_tighter_than_and_tighter_than_or() {
+        // (not ERROR) and web2, not not (ERROR and web2).
+        assert!(messages("not level=ERROR and host=web2").is_empty());
+        // INFO or (ERROR and web2).
+        assert_eq!(
+            messages("level=INFO or level=ERROR and host=web2"),
+            ["started", "db timeout"]
+        );
+        assert_eq!(messages("(level=INFO or level=ERROR) and host=web2"), ["db timeout"]);
+        assert_eq!(messages("not not level=WARN"), ["slow"]);
+    }
+
+    #[test]
+    fn stages_must_come_in_order() {
+        assert_eq!(error("level=ERROR | limit 1 | count"), "count must be the first stage");
+        assert_eq!(error("count | count"), "count must be the first stage");
+        assert_eq!(error("limit 1 | top 3 by host"), "top N by ... must be the first stage");
+        assert_eq!(error("level=ERROR | top 3"), "top needs a count before it, or use top N by ...");
+        assert_eq!(error("level=ERROR | sort"), "unknown stage 'sort' (use count, top or limit)");
+        assert_eq!(error("limit many"), "limit expects a number, got 'many'");
+        assert_eq!(error("time>=yesterday"), "'yesterday' is not a time");
+        assert!(error("msg~\"(\"").starts_with("invalid regex for 'msg'"));
+
+        // A stage name followed by an operator is a field.
+        let query: Query = "count>1".parse().unwrap();
+        assert!(query.is_filter());
+    }
+
+    #[test]
+    fn top_n_by_counts_and_keeps_the_largest() {
+        assert_eq!(run("top 1 by host"), [["web1", "2"]]);
+        assert_eq!(run("count by level | top 1"), [["ERROR", "2"]]);
+        assert_eq!(run("level=ERROR | count by host"), [["-", "1"], ["web2", "1"]]);
+        assert_eq!(run("count by host | limit 2").len(), 2);
+    }
+
+    #[test]
+    fn compares_numbers_times_and_text() {
+        // As numbers, 1000 is not below 503; as text it would be.
+        assert_eq!(messages("status>=500"), ["db timeout", "timeout again"]);
+        assert_eq!(messages("status<503"), ["started", "slow"]);
+        assert_eq!(messages("host>web1"), ["db timeout"]);
+        // Times in another layout compare as times.
+        assert_eq!(
+            messages("time>=\"2023-01-01 10:00:40\" and time<\"2023-01-01 10:02:00\""),
+            ["db timeout", "slow"]
+        );
+        assert_eq!(messages("msg~^timeout"), ["timeout again"]);
+    }
+
+    #[test]
+    fn missing_fields_only_match_negations() {
+        assert_eq!(messages("host!=web1"), ["db timeout", "timeout again"]);
+        assert_eq!(messages("host!~web"), ["timeout again"]);
+        assert!(!messages("host~.").contains(&"timeout again".to_string()));
+        assert!(messages("region<zzz").is_empty());
+        assert!(messages("region=\"\"").is_empty());
+    }
+
+    #[test]
+    fn counts_by_time_bucket() {
+        assert_eq!(
+            run("count by minute"),
+            [
+                ["2023-01-01 10:00", "2"],
+                ["2023-01-01 10:01", "1"],
+                ["2023-01-01 10:02", "1"],
+            ]
+        );
+        assert_eq!(
+            run("level=ERROR | count by level, hour"),
+            [["ERROR", "2023-01-01 10:00", "2"]]
+        );
+        assert_eq!(run("count by day"), [["2023-01-01", "4"]]);
+        // A keyless count answers even when nothing matches.
+        assert_eq!(run("level=DEBUG | count"), [["0"]]);
+    }
+
+    fn write(table: &Table, output: Output) -> String {
+        let mut out = Vec::new();
+        table.write(output, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn writes_table_csv_and_json() {
+        let table = Table {
+            columns: vec!["message".into(), "count".into()],
+            rows: vec![
+                vec![Cell::Text("two\nlines".into()), Cell::Number(12)],
+                vec![Cell::Text("a, \"b\"".into()), Cell::Number(3)],
+            ],
+        };
+        assert_eq!(
+            write(&table, Output::Table),
+            "message     count\n\
+             ----------  -----\n\
+             two\\nlines     12\n\
+             a, \"b\"          3\n\
+             (2 rows)\n"
+        );
+        assert_eq!(
+            write(&table, Output::Csv),
+            "message,count\n\"two\nlines\",12\n\"a, \"\"b\"\"\",3\n"
+        );
+        assert_eq!(
+            write(&table, Output::Json),
+            "[\n  {\"message\": \"two\\nlines\", \"count\": 12},\n  \
+             {\"message\": \"a, \\\"b\\\"\", \"count\": 3}\n]\n"
+        );
+
+        let empty = Table {
+            columns: vec!["count".into()],
+            rows: vec![vec![Cell::Number(0)]],
+        };
+        assert_eq!(write(&empty, Output::Table), "count\n-----\n    0\n(1 row)\n");
+        assert_eq!(write(&empty, Output::Json), "[\n  {\"count\": 0}\n]\n");
+        assert_eq!(
+            "xml".parse::<Output>().unwrap_err(),
+            "--output expects table, csv or json, got 'xml'"
+        );
+    }
+}
@@ -0,0 +1,69 @@
+// src/bin/read_compare.rs
+// Concurrent get_setting: the old global Mutex against lock-free snapshots.
+// Run with: cargo run --release --bin read_compare
+
+use sigletone::config;
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::sync::{LazyLock, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const READS_PER_THREAD: usize = 1_000_000;
+
+// ------------------ MUTEX VERSION ------------------
+// How every read worked before: lock the singleton, then look up.
+static LOCKED: LazyLock<Mutex<HashMap<String, String>>> = LazyLock::new(|| {
+    let mut settings = HashMap::new();
+    settings.insert("theme".to_string(), "dark".to_string());
+    settings.insert("language".to_string(), "en".to_string());
+    settings.insert("timeout_seconds".to_string(), "30".to_string());
+    Mutex::new(settings)
+});
+
+fn mutex_get(key: &str) -> Option<String> {
+    LOCKED.lock().unwrap().get(key).cloned()
+}
+
+// ------------------ HARNESS ------------------
+fn run(threads: usize, read: fn(&str) -> Option<String>) -> Duration {
+    let start = Instant::now();
+    let handles: Vec<_> = (0..threads)
+        .map(|_| {
+            thread::spawn(move || {
+                for _ in 0..READS_PER_THREAD {
+                    black_box(read(black_box("theme")));
+                }
+            })
+        })
+        .collect();
+    for h in handles {
+        h.join().unwrap();
+    }
+    start.elapsed()
+}
+
+// ------------------ MAIN ------------------
+fn main() {
+    // Initialize both outside the timed part.
+    mutex_get("theme");
+    config::get_setting("theme");
+
+    let cores = thread::available_parallelism().map_or(4, |n| n.get());
+    println!("{} reads per thread, {} cores", READS_PER_THREAD, cores);
+    println!("{:>8} {:>12} {:>12} {:>8}", "threads", "mutex", "lock-free", "speedup");
+
+    let mut threads = 1;
+    while threads <= cores.max(2) * 2 {
+        let mutex = run(threads, mutex_get);
+        let lock_free = run(threads, config::get_setting);
+        println!(
+            "{:>8} {:>12.2?} {:>12.2?} {:>7.1}x",
+            threads,
+            mutex,
+            lock_free,
+            mutex.as_secs_f64() / lock_free.as_secs_f64()
+        );
+        threads *= 2;
+    }
+}
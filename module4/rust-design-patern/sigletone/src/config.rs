@@ -1,58 +1,318 @@
 // src/config.rs
+use crate::error::ConfigError;
+use crate::schema::{self, Kind, Schema};
+use arc_swap::ArcSwap;
 use std::collections::HashMap;
-use std::sync::{LazyLock, Mutex};
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
 
-// The global singleton instance (private to this module)
-static CONFIG: LazyLock<Mutex<ConfigurationManager>> = LazyLock::new(|| {
-    Mutex::new(ConfigurationManager::new())
-});
+/// Settings file used unless the `APP_CONFIG` environment variable names another.
+pub const DEFAULT_PATH: &str = "app.conf";
+
+pub type Settings = HashMap<String, String>;
+
+// The global singleton instance (private to this module). No outer lock:
+// reads load the current snapshot, writes swap in a modified copy.
+static CONFIG: LazyLock<ConfigurationManager> = LazyLock::new(ConfigurationManager::new);
+
+/// Sent to subscribers whenever settings change, by reload or `set_setting`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigChange {
+    /// Added, removed and modified keys, sorted.
+    pub keys: Vec<String>,
+}
 
 /// The configuration manager struct
 pub struct ConfigurationManager {
-    settings: HashMap<String, String>,
+    path: PathBuf,
+    schema: Schema,
+    // Swapped as a whole, so a snapshot is always one complete version.
+    settings: ArcSwap<Settings>,
+    // Modification time and length of the file when it was last read.
+    // Only reloads and the watcher touch it, never readers.
+    stamp: Mutex<Option<(SystemTime, u64)>>,
+    subscribers: Mutex<Vec<Sender<ConfigChange>>>,
 }
 
 impl ConfigurationManager {
     // Private constructor — only callable inside this file
     fn new() -> Self {
-        let mut settings = HashMap::new();
-        // Simulate loading from file/env — replace with real logic if needed
-        settings.insert("theme".to_string(), "dark".to_string());
-        settings.insert("language".to_string(), "en".to_string());
-        settings.insert("timeout_seconds".to_string(), "30".to_string());
+        let path = config_path();
+        let manager = ConfigurationManager {
+            settings: ArcSwap::from_pointee(defaults()),
+            path,
+            schema: app_schema(),
+            stamp: Mutex::new(None),
+            subscribers: Mutex::new(Vec::new()),
+        };
 
-        println!("ConfigurationManager initialized with {} settings", settings.len());
+        match manager.reload() {
+            Ok(_) => println!("Loaded settings from {}", manager.path.display()),
+            Err(err) => println!("Using built-in defaults ({}: {})", manager.path.display(), err),
+        }
+        println!("ConfigurationManager initialized with {} settings", manager.settings.load().len());
+
+        manager
+    }
+
+    /// Public method to read a setting; lock-free
+    pub fn get_setting(&self, key: &str) -> Option<String> {
+        self.settings.load().get(key).cloned()
+    }
 
-        ConfigurationManager { settings }
+    /// Parses a setting with `FromStr`, e.g. `get::<u64>("max_connections")`.
+    pub fn get<T>(&self, key: &str) -> Result<T, ConfigError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        schema::parse(key, &self.require(key)?)
     }
 
-    /// Public method to read a setting
-    pub fn get_setting(&self, key: &str) -> Option<&String> {
-        self.settings.get(key)
+    pub fn get_bool(&self, key: &str) -> Result<bool, ConfigError> {
+        schema::parse_bool(key, &self.require(key)?)
     }
 
-    /// Optional: Add a setter if you need to modify config at runtime
-    pub fn set_setting(&mut self, key: String, value: String) {
-        self.settings.insert(key, value);
+    /// See `schema::parse_duration`; a bare number is seconds.
+    pub fn get_duration(&self, key: &str) -> Result<Duration, ConfigError> {
+        schema::parse_duration(key, &self.require(key)?)
+    }
+
+    fn require(&self, key: &str) -> Result<String, ConfigError> {
+        self.get_setting(key).ok_or_else(|| ConfigError::Missing {
+            key: key.to_string(),
+        })
+    }
+
+    /// All settings as one consistent version; later reloads do not affect it.
+    pub fn snapshot(&self) -> Arc<Settings> {
+        self.settings.load_full()
+    }
+
+    /// Optional: Add a setter if you need to modify config at runtime.
+    /// Copy-on-write: readers keep the version they loaded while a modified
+    /// copy is swapped in.
+    pub fn set_setting(&self, key: String, value: String) -> Result<(), ConfigError> {
+        let mut current = self.settings.load_full();
+        loop {
+            let mut settings = Settings::clone(&current);
+            settings.insert(key.clone(), value.clone());
+            self.schema.validate(&settings)?;
+
+            // Retry on top of a concurrent write instead of losing it.
+            let new = Arc::new(settings);
+            let previous = self.settings.compare_and_swap(&current, Arc::clone(&new));
+            if Arc::ptr_eq(&previous, &current) {
+                self.notify(&current, &new);
+                break;
+            }
+            current = arc_swap::Guard::into_inner(previous);
+        }
         println!("Setting updated dynamically");
+        Ok(())
+    }
+
+    pub fn subscribe(&self) -> Receiver<ConfigChange> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribers.lock().unwrap().push(tx);
+        rx
+    }
+
+    /// Reads the file again; file values sit on top of the built-in defaults.
+    /// On error, including schema violations, the current settings stay.
+    pub fn reload(&self) -> Result<Option<ConfigChange>, ConfigError> {
+        let io_error = |source| ConfigError::Io {
+            path: self.path.clone(),
+            source,
+        };
+        // Taken before reading, so a write that races the read is seen next poll.
+        let stamp = file_stamp(&self.path).map_err(io_error)?;
+        let content = fs::read_to_string(&self.path).map_err(io_error)?;
+
+        let mut settings = defaults();
+        settings.extend(parse_settings(&self.path, &content)?);
+        *self.stamp.lock().unwrap() = Some(stamp);
+        self.schema.validate(&settings)?;
+
+        // The file replaces everything, so there is nothing to merge with.
+        let new = Arc::new(settings);
+        let old = self.settings.swap(Arc::clone(&new));
+        Ok(self.notify(&old, &new))
+    }
+
+    // One poll of the watcher: reload if the file looks different.
+    fn poll(&self) {
+        let stamp = file_stamp(&self.path).ok();
+        if stamp == *self.stamp.lock().unwrap() {
+            return;
+        }
+        if let Err(err) = self.reload() {
+            eprintln!("Config reload failed, keeping previous settings: {}", err);
+            // Do not report the same broken file on every poll.
+            *self.stamp.lock().unwrap() = stamp;
+        }
+    }
+
+    fn notify(&self, old: &Settings, new: &Settings) -> Option<ConfigChange> {
+        let mut keys: Vec<String> = new
+            .iter()
+            .filter(|(key, value)| old.get(*key) != Some(value))
+            .map(|(key, _)| key.clone())
+            .chain(old.keys().filter(|key| !new.contains_key(*key)).cloned())
+            .collect();
+        if keys.is_empty() {
+            return None;
+        }
+        keys.sort();
+
+        let change = ConfigChange { keys };
+        // Dropped receivers unsubscribe.
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|tx| tx.send(change.clone()).is_ok());
+        Some(change)
+    }
+}
+
+fn config_path() -> PathBuf {
+    env::var_os("APP_CONFIG").map_or_else(|| PathBuf::from(DEFAULT_PATH), PathBuf::from)
+}
+
+// What the application relies on; checked on every load and update.
+fn app_schema() -> Schema {
+    Schema::new()
+        .required("theme", Kind::OneOf(&["dark", "light", "solarized"]))
+        .required("language", Kind::Text)
+        .required(
+            "timeout_seconds",
+            Kind::Duration {
+                min: Duration::from_secs(1),
+                max: Duration::from_secs(3600),
+            },
+        )
+        .optional("debug", Kind::Bool)
+        .optional("max_connections", Kind::Integer { min: 1, max: 10_000 })
+}
+
+fn defaults() -> Settings {
+    let mut settings = HashMap::new();
+    settings.insert("theme".to_string(), "dark".to_string());
+    settings.insert("language".to_string(), "en".to_string());
+    settings.insert("timeout_seconds".to_string(), "30".to_string());
+    settings
+}
+
+fn file_stamp(path: &Path) -> std::io::Result<(SystemTime, u64)> {
+    let meta = fs::metadata(path)?;
+    Ok((meta.modified()?, meta.len()))
+}
+
+// `key = value` per line; blank lines and `#` comments are skipped.
+fn parse_settings(path: &Path, content: &str) -> Result<Settings, ConfigError> {
+    let mut settings = HashMap::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(ConfigError::Syntax {
+                path: path.to_path_buf(),
+                line: number + 1,
+            });
+        };
+        settings.insert(key.trim().to_string(), value.trim().to_string());
     }
+    Ok(settings)
 }
 
 // Public functions to access the singleton safely
 
-/// Get a locked guard to the singleton (read-only or mutable access)
-pub fn instance() -> std::sync::MutexGuard<'static, ConfigurationManager> {
-    CONFIG.lock().unwrap()
+/// Get the singleton; safe to share across threads without locking
+pub fn instance() -> &'static ConfigurationManager {
+    &CONFIG
 }
 
-/// Convenience function to read a setting without manually locking
+/// Convenience function to read a setting
 pub fn get_setting(key: &str) -> Option<String> {
-    let config = instance();
-    config.get_setting(key).cloned()
+    instance().get_setting(key)
 }
 
 /// Convenience function to set a setting (if needed)
-pub fn set_setting(key: String, value: String) {
-    let mut config = instance();
-    config.set_setting(key, value);
-}
\ No newline at end of file
+pub fn set_setting(key: String, value: String) -> Result<(), ConfigError> {
+    instance().set_setting(key, value)
+}
+
+pub fn get<T>(key: &str) -> Result<T, ConfigError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    instance().get(key)
+}
+
+pub fn get_bool(key: &str) -> Result<bool, ConfigError> {
+    instance().get_bool(key)
+}
+
+pub fn get_duration(key: &str) -> Result<Duration, ConfigError> {
+    instance().get_duration(key)
+}
+
+/// Call once at startup to fail fast: reads and validates the settings
+/// file instead of quietly falling back to the defaults.
+pub fn init() -> Result<(), ConfigError> {
+    instance().reload().map(drop)
+}
+
+/// Receive a `ConfigChange` for every change from now on.
+pub fn subscribe() -> Receiver<ConfigChange> {
+    instance().subscribe()
+}
+
+// -----------------------------
+// File Watching
+// -----------------------------
+/// Polls the settings file's modification time (and length) every
+/// `interval` and reloads when it changes. Polling needs no OS support and
+/// works on any filesystem. Stops when dropped.
+pub fn watch(interval: Duration) -> Watcher {
+    let stop = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&stop);
+
+    let handle = thread::spawn(move || {
+        while !flag.load(Ordering::Relaxed) {
+            instance().poll();
+            thread::park_timeout(interval);
+        }
+    });
+
+    Watcher {
+        stop,
+        handle: Some(handle),
+    }
+}
+
+pub struct Watcher {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            handle.thread().unpark();
+            let _ = handle.join();
+        }
+    }
+}
@@ -0,0 +1,41 @@
+// src/error.rs
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Cannot read {}: {source}", .path.display())]
+    Io { path: PathBuf, source: io::Error },
+
+    #[error("{}:{line}: expected `key = value`", .path.display())]
+    Syntax { path: PathBuf, line: usize },
+
+    #[error("Missing setting: {key}")]
+    Missing { key: String },
+
+    #[error("Invalid value: {key} = {value} ({reason})")]
+    InvalidValue {
+        key: String,
+        value: String,
+        reason: String,
+    },
+
+    #[error("Out of range: {key} = {value} (allowed: {allowed})")]
+    OutOfRange {
+        key: String,
+        value: String,
+        allowed: String,
+    },
+
+    #[error("{} invalid settings: {}", .0.len(), list(.0))]
+    Invalid(Vec<ConfigError>),
+}
+
+fn list(errors: &[ConfigError]) -> String {
+    errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
@@ -0,0 +1,7 @@
+// src/lib.rs
+// The configuration singleton, shared by the demo and the read_compare
+// benchmark
+
+pub mod config;
+pub mod error;
+pub mod schema;
@@ -1,21 +1,56 @@
 // src/main.rs
-mod config;  // Imports the config module
-
-use config::{get_setting, instance, set_setting};
+use sigletone::config::{
+    get, get_bool, get_duration, get_setting, init, instance, set_setting, subscribe, watch,
+};
+use std::env;
+use std::time::Duration;
 
 fn main() {
-    // First access → triggers lazy initialization
-    
+    // Settings file for the demo, in the temp directory so that a real
+    // app.conf (or whatever APP_CONFIG names) is never overwritten
+    let path = env::temp_dir().join(format!("sigletone-demo-{}.conf", std::process::id()));
+    // SAFETY: no other thread is running yet, and none reads the environment.
+    unsafe { env::set_var("APP_CONFIG", &path) };
+    std::fs::write(&path, "# demo settings\ntheme = dark\nlanguage = en\nmax_connections = 100\n")
+        .expect("cannot write demo settings");
+
+    // First access → triggers lazy initialization; init() fails fast on a bad file
+    if let Err(err) = init() {
+        eprintln!("Invalid configuration: {}", err);
+        std::process::exit(1);
+    }
+
     println!("Initial theme: {}", get_setting("theme").unwrap_or("unknown".to_string()));
-    // Direct access via lock (useful when you need multiple reads)
+    // One snapshot for several reads that must agree with each other
     {
-        let config = instance();
-        println!("Language: {}", config.get_setting("language").unwrap());
-        println!("Timeout: {}", config.get_setting("timeout_seconds").unwrap());
+        let settings = instance().snapshot();
+        println!("Language: {}", settings["language"]);
+        println!("Timeout: {}", settings["timeout_seconds"]);
+    }
+
+    // Typed accessors parse once and explain what is wrong
+    match get::<u64>("max_connections") {
+        Ok(n) => println!("Max connections: {}", n),
+        Err(err) => println!("Error: {}", err),
+    }
+    match get_duration("timeout_seconds") {
+        Ok(timeout) => println!("Timeout as duration: {:?}", timeout),
+        Err(err) => println!("Error: {}", err),
+    }
+    match get_bool("debug") {
+        Ok(debug) => println!("Debug: {}", debug),
+        Err(err) => println!("Error: {}", err),
     }
 
     // Demonstrate dynamic update (optional feature)
-    set_setting("theme".to_string(), "light".to_string());
+    set_setting("theme".to_string(), "light".to_string()).expect("valid theme");
+
+    // Updates are checked against the schema and rejected as a whole
+    for (key, value) in [("theme", "neon"), ("timeout_seconds", "0"), ("debug", "maybe")] {
+        if let Err(err) = set_setting(key.to_string(), value.to_string()) {
+            println!("Rejected {} = {}: {}", key, value, err);
+        }
+    }
 
     // Prove it's the same instance
     println!("Updated theme: {}", get_setting("theme").unwrap());
@@ -31,6 +66,32 @@ fn main() {
 
     handle.join().unwrap();
 
+    // Hot reload: edit the file while a watcher polls it
+    let changes = subscribe();
+    let _watcher = watch(Duration::from_millis(100));
+
+    // Write then rename, so the watcher never sees a half-written file
+    let staged = path.with_extension("conf.tmp");
+    std::fs::write(
+        &staged,
+        "# demo settings\ntheme = solarized\nlanguage = de\ntimeout_seconds = 45\n",
+    )
+    .expect("cannot update demo settings");
+    std::fs::rename(&staged, &path).expect("cannot update demo settings");
+
+    match changes.recv_timeout(Duration::from_secs(5)) {
+        Ok(change) => {
+            println!("Reloaded, changed keys: {}", change.keys.join(", "));
+            let snapshot = instance().snapshot();
+            println!(
+                "Now: theme = {}, language = {}, timeout = {}",
+                snapshot["theme"], snapshot["language"], snapshot["timeout_seconds"]
+            );
+        }
+        Err(_) => println!("No reload within 5 seconds"),
+    }
+    let _ = std::fs::remove_file(&path);
+
     // Try to create another instance? → Impossible from here!
     // config::ConfigurationManager::new();  // Compile error: private
-}
\ No newline at end of file
+}
@@ -0,0 +1,157 @@
+// src/schema.rs
+use crate::config::Settings;
+use crate::error::ConfigError;
+use std::fmt::Display;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// What a setting's value must look like.
+#[derive(Debug, Clone)]
+pub enum Kind {
+    Text,
+    Bool,
+    Integer { min: i64, max: i64 },
+    Duration { min: Duration, max: Duration },
+    OneOf(&'static [&'static str]),
+}
+
+#[derive(Debug, Clone)]
+struct Field {
+    key: &'static str,
+    required: bool,
+    kind: Kind,
+}
+
+/// Keys the application relies on; settings not listed are left alone.
+#[derive(Debug, Clone, Default)]
+pub struct Schema {
+    fields: Vec<Field>,
+}
+
+impl Schema {
+    pub fn new() -> Self {
+        Schema::default()
+    }
+
+    pub fn required(mut self, key: &'static str, kind: Kind) -> Self {
+        self.fields.push(Field {
+            key,
+            required: true,
+            kind,
+        });
+        self
+    }
+
+    pub fn optional(mut self, key: &'static str, kind: Kind) -> Self {
+        self.fields.push(Field {
+            key,
+            required: false,
+            kind,
+        });
+        self
+    }
+
+    /// Reports every problem at once, not just the first.
+    pub fn validate(&self, settings: &Settings) -> Result<(), ConfigError> {
+        let mut errors: Vec<ConfigError> = self
+            .fields
+            .iter()
+            .filter_map(|field| field.check(settings).err())
+            .collect();
+
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.remove(0)),
+            _ => Err(ConfigError::Invalid(errors)),
+        }
+    }
+}
+
+impl Field {
+    fn check(&self, settings: &Settings) -> Result<(), ConfigError> {
+        let Some(value) = settings.get(self.key) else {
+            if self.required {
+                return Err(ConfigError::Missing {
+                    key: self.key.to_string(),
+                });
+            }
+            return Ok(());
+        };
+
+        let out_of_range = |allowed: String| ConfigError::OutOfRange {
+            key: self.key.to_string(),
+            value: value.clone(),
+            allowed,
+        };
+
+        match &self.kind {
+            Kind::Text => {}
+            Kind::Bool => {
+                parse_bool(self.key, value)?;
+            }
+            Kind::Integer { min, max } => {
+                let n: i64 = parse(self.key, value)?;
+                if n < *min || n > *max {
+                    return Err(out_of_range(format!("{}..={}", min, max)));
+                }
+            }
+            Kind::Duration { min, max } => {
+                let duration = parse_duration(self.key, value)?;
+                if duration < *min || duration > *max {
+                    return Err(out_of_range(format!("{:?}..={:?}", min, max)));
+                }
+            }
+            Kind::OneOf(choices) => {
+                if !choices.contains(&value.as_str()) {
+                    return Err(out_of_range(format!("one of {}", choices.join(", "))));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// -----------------------------
+// Typed Parsing
+// -----------------------------
+fn invalid(key: &str, value: &str, reason: impl Display) -> ConfigError {
+    ConfigError::InvalidValue {
+        key: key.to_string(),
+        value: value.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+pub fn parse<T>(key: &str, value: &str) -> Result<T, ConfigError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    value.parse().map_err(|err| invalid(key, value, err))
+}
+
+pub fn parse_bool(key: &str, value: &str) -> Result<bool, ConfigError> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Ok(true),
+        "false" | "no" | "off" | "0" => Ok(false),
+        _ => Err(invalid(key, value, "expected true/false, yes/no, on/off or 1/0")),
+    }
+}
+
+/// `250ms`, `30s`, `5m` or `1h`; a bare number means seconds.
+pub fn parse_duration(key: &str, value: &str) -> Result<Duration, ConfigError> {
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+
+    let expected = "expected a duration like 30, 30s, 250ms, 5m or 1h";
+    let n: u64 = number.parse().map_err(|_| invalid(key, value, expected))?;
+    match unit.trim() {
+        "ms" => Ok(Duration::from_millis(n)),
+        "" | "s" => Ok(Duration::from_secs(n)),
+        "m" => Ok(Duration::from_secs(n.saturating_mul(60))),
+        "h" => Ok(Duration::from_secs(n.saturating_mul(3600))),
+        _ => Err(invalid(key, value, expected)),
+    }
+}